@@ -0,0 +1,81 @@
+use std::{
+    fs::File,
+    io::{LineWriter, Write},
+};
+
+use lazy_static::lazy_static;
+use log::error;
+use parking_lot::Mutex;
+
+use crate::bridge::RedrawEvent;
+
+// Written to the current directory, like the log file
+const EVENT_LOG_PATH: &str = "neovide_events.jsonl";
+
+lazy_static! {
+    static ref EVENT_LOG: Mutex<Option<LineWriter<File>>> = Mutex::new(
+        File::create(EVENT_LOG_PATH)
+            .map(LineWriter::new)
+            .map_err(|error| error!("Could not create {}: {}", EVENT_LOG_PATH, error))
+            .ok()
+    );
+}
+
+/// Appends the event to the event log as a line of json. Feeding the deserialized lines to
+/// Editor::apply_events rebuilds the editor state without a running Neovim.
+pub fn record_event(event: &RedrawEvent) {
+    if let Some(event_log) = EVENT_LOG.lock().as_mut() {
+        if let Err(error) = write_event(event_log, event) {
+            error!("Could not record redraw event: {}", error);
+        }
+    }
+}
+
+fn write_event(writer: &mut impl Write, event: &RedrawEvent) -> std::io::Result<()> {
+    serde_json::to_writer(&mut *writer, event)?;
+    writeln!(writer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bridge::GridLineCell, editor::Editor};
+
+    #[test]
+    fn recorded_events_read_back_one_per_line() {
+        let events = vec![
+            RedrawEvent::Resize {
+                grid: 1,
+                width: 3,
+                height: 1,
+            },
+            RedrawEvent::GridLine {
+                grid: 1,
+                row: 0,
+                column_start: 0,
+                cells: vec![GridLineCell {
+                    text: "abc".to_owned(),
+                    highlight_id: None,
+                    repeat: None,
+                }],
+                wrap: false,
+            },
+        ];
+
+        let mut log = Vec::new();
+        for event in &events {
+            write_event(&mut log, event).unwrap();
+        }
+
+        let read_events: Vec<RedrawEvent> = String::from_utf8(log)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(format!("{:?}", read_events), format!("{:?}", events));
+
+        let mut editor = Editor::new();
+        editor.apply_events(&read_events);
+        assert_eq!(editor.to_text_lines(1, false), Some(vec!["abc".to_owned()]));
+    }
+}
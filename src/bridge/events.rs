@@ -6,6 +6,8 @@ use std::{
 
 use log::debug;
 use rmpv::Value;
+#[cfg(feature = "event-recording")]
+use serde::{Deserialize, Serialize};
 use skia_safe::Color4f;
 
 use crate::editor::{Colors, CursorMode, CursorShape, Style, UnderlineStyle};
@@ -51,6 +53,7 @@ impl error::Error for ParseError {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "event-recording", derive(Serialize, Deserialize))]
 pub struct GridLineCell {
     pub text: String,
     pub highlight_id: Option<u64>,
@@ -59,7 +62,22 @@ pub struct GridLineCell {
 
 pub type StyledContent = Vec<(u64, String)>;
 
+/// A completion candidate sent by popupmenu_show. See `:h complete-items` for what each field
+/// holds.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "event-recording", derive(Serialize, Deserialize))]
+pub struct PopupMenuItem {
+    pub word: String,
+    pub kind: String,
+    pub menu: String,
+    pub info: String,
+}
+
+/// Tab page or buffer handles paired with their display name, as sent by tabline_update.
+pub type TablineEntries = Vec<(Value, String)>;
+
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "event-recording", derive(Serialize, Deserialize))]
 pub enum MessageKind {
     Unknown,
     Confirm,
@@ -97,6 +115,7 @@ impl MessageKind {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "event-recording", derive(Serialize, Deserialize))]
 pub enum GuiOption {
     ArabicShape(bool),
     AmbiWidth(String),
@@ -104,7 +123,7 @@ pub enum GuiOption {
     GuiFont(String),
     GuiFontSet(String),
     GuiFontWide(String),
-    LineSpace(u64),
+    LineSpace(i64),
     Pumblend(u64),
     ShowTabLine(u64),
     TermGuiColors(bool),
@@ -112,6 +131,7 @@ pub enum GuiOption {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "event-recording", derive(Serialize, Deserialize))]
 pub enum WindowAnchor {
     NorthWest,
     NorthEast,
@@ -120,6 +140,7 @@ pub enum WindowAnchor {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "event-recording", derive(Serialize, Deserialize))]
 pub enum EditorMode {
     // The set of modes reported will change in new versions of Nvim, for
     // instance more sub-modes and temporary states might be represented as
@@ -135,10 +156,20 @@ pub enum EditorMode {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "event-recording", derive(Serialize, Deserialize))]
 pub enum RedrawEvent {
     SetTitle {
         title: String,
     },
+    SetIcon {
+        icon: String,
+    },
+    TablineUpdate {
+        current_tab: Value,
+        tabs: TablineEntries,
+        current_buffer: Option<Value>,
+        buffers: TablineEntries,
+    },
     ModeInfoSet {
         cursor_modes: Vec<CursorMode>,
     },
@@ -153,24 +184,32 @@ pub enum RedrawEvent {
     MouseOff,
     BusyStart,
     BusyStop,
+    Suspend,
+    Bell,
+    VisualBell,
     Flush,
     Resize {
         grid: u64,
         width: u64,
         height: u64,
     },
+    /// The cterm colors are palette colors, for the editor to fall back to when 'termguicolors'
+    /// is off.
     DefaultColorsSet {
         colors: Colors,
+        cterm_colors: Colors,
     },
     HighlightAttributesDefine {
         id: u64,
         style: Style,
+        cterm_colors: Colors,
     },
     GridLine {
         grid: u64,
         row: u64,
         column_start: u64,
         cells: Vec<GridLineCell>,
+        wrap: bool,
     },
     Clear {
         grid: u64,
@@ -230,6 +269,7 @@ pub enum RedrawEvent {
         current_line: f64,
         current_column: f64,
         line_count: Option<f64>,
+        scroll_delta: Option<i64>,
     },
     CommandLineShow {
         content: StyledContent,
@@ -256,6 +296,17 @@ pub enum RedrawEvent {
         line: StyledContent,
     },
     CommandLineBlockHide,
+    PopupMenuShow {
+        items: Vec<PopupMenuItem>,
+        selected: Option<u64>,
+        row: u64,
+        column: u64,
+        grid: Option<u64>,
+    },
+    PopupMenuSelect {
+        selected: Option<u64>,
+    },
+    PopupMenuHide,
     MessageShow {
         kind: MessageKind,
         content: StyledContent,
@@ -368,6 +419,52 @@ fn parse_set_title(set_title_arguments: Vec<Value>) -> Result<RedrawEvent> {
     })
 }
 
+fn parse_set_icon(set_icon_arguments: Vec<Value>) -> Result<RedrawEvent> {
+    let [icon] = extract_values(set_icon_arguments)?;
+
+    Ok(RedrawEvent::SetIcon {
+        icon: parse_string(icon)?,
+    })
+}
+
+fn parse_tabline_entries(entries: Value, handle_name: &str) -> Result<TablineEntries> {
+    parse_array(entries)?
+        .into_iter()
+        .map(|entry| {
+            let mut handle = Value::Nil;
+            let mut name = String::new();
+
+            for (key, value) in parse_map(entry)? {
+                match parse_string(key)?.as_str() {
+                    "name" => name = parse_string(value)?,
+                    key if key == handle_name => handle = value,
+                    _ => {}
+                }
+            }
+
+            Ok((handle, name))
+        })
+        .collect()
+}
+
+fn parse_tabline_update(tabline_update_arguments: Vec<Value>) -> Result<RedrawEvent> {
+    let ([current_tab, tabs], [current_buffer, buffers]) =
+        extract_values_with_optional(tabline_update_arguments)?;
+
+    let buffers = if let Some(buffers) = buffers {
+        parse_tabline_entries(buffers, "buffer")?
+    } else {
+        Vec::new()
+    };
+
+    Ok(RedrawEvent::TablineUpdate {
+        current_tab,
+        tabs: parse_tabline_entries(tabs, "tab")?,
+        current_buffer,
+        buffers,
+    })
+}
+
 fn parse_mode_info_set(mode_info_set_arguments: Vec<Value>) -> Result<RedrawEvent> {
     let [_cursor_style_enabled, mode_info] = extract_values(mode_info_set_arguments)?;
 
@@ -398,6 +495,15 @@ fn parse_mode_info_set(mode_info_set_arguments: Vec<Value>) -> Result<RedrawEven
                 "attr_id" => {
                     mode_info.style_id = Some(parse_u64(value)?);
                 }
+                "name" => {
+                    mode_info.name = Some(parse_string(value)?);
+                }
+                "short_name" => {
+                    mode_info.short_name = Some(parse_string(value)?);
+                }
+                "mouse_shape" => {
+                    mode_info.mouse_shape = Some(parse_u64(value)?);
+                }
                 _ => {}
             }
         }
@@ -421,7 +527,7 @@ fn parse_option_set(option_set_arguments: Vec<Value>) -> Result<RedrawEvent> {
             "guifont" => GuiOption::GuiFont(parse_string(value)?),
             "guifontset" => GuiOption::GuiFontSet(parse_string(value)?),
             "guifontwide" => GuiOption::GuiFontWide(parse_string(value)?),
-            "linespace" => GuiOption::LineSpace(parse_u64(value)?),
+            "linespace" => GuiOption::LineSpace(parse_i64(value)?),
             "pumblend" => GuiOption::Pumblend(parse_u64(value)?),
             "showtabline" => GuiOption::ShowTabLine(parse_u64(value)?),
             "termguicolors" => GuiOption::TermGuiColors(parse_bool(value)?),
@@ -457,15 +563,86 @@ fn parse_grid_resize(grid_resize_arguments: Vec<Value>) -> Result<RedrawEvent> {
     })
 }
 
+// The 16 system colors as xterm draws them by default.
+const CTERM_SYSTEM_COLORS: [u64; 16] = [
+    0x000000, 0xcd0000, 0x00cd00, 0xcdcd00, 0x0000ee, 0xcd00cd, 0x00cdcd, 0xe5e5e5, 0x7f7f7f,
+    0xff0000, 0x00ff00, 0xffff00, 0x5c5cff, 0xff00ff, 0x00ffff, 0xffffff,
+];
+const CTERM_CUBE_LEVELS: [u64; 6] = [0x00, 0x5f, 0x87, 0xaf, 0xd7, 0xff];
+
+// Converts an index of the xterm 256 color palette: the 16 system colors, followed by a 6x6x6
+// color cube and a ramp of 24 grays.
+fn cterm_color(index: u64) -> Option<Color4f> {
+    let packed_color = match index {
+        0..=15 => CTERM_SYSTEM_COLORS[index as usize],
+        16..=231 => {
+            let cube_index = index - 16;
+            let r = CTERM_CUBE_LEVELS[(cube_index / 36) as usize];
+            let g = CTERM_CUBE_LEVELS[(cube_index / 6 % 6) as usize];
+            let b = CTERM_CUBE_LEVELS[(cube_index % 6) as usize];
+            (r << 16) | (g << 8) | b
+        }
+        232..=255 => {
+            let level = 8 + (index - 232) * 10;
+            (level << 16) | (level << 8) | level
+        }
+        _ => return None,
+    };
+    Some(unpack_color(packed_color))
+}
+
+// Unlike the rgb ones, the default cterm colors are sent as the palette index plus one, with 0
+// meaning unset.
+fn parse_default_cterm_color(color: Value) -> Result<Option<Color4f>> {
+    Ok(u64::try_from(parse_i64(color)? - 1)
+        .ok()
+        .and_then(cterm_color))
+}
+
+// Highlights only defined with ctermfg and ctermbg have no rgb colors, so their palette colors
+// can be used instead.
+fn parse_cterm_colors(cterm_map: Value) -> Result<Colors> {
+    let mut colors = Colors::new(None, None, None);
+
+    for (name, value) in parse_map(cterm_map)? {
+        match (parse_string(name)?.as_str(), value) {
+            ("foreground", Value::Integer(index)) => {
+                colors.foreground = index.as_u64().and_then(cterm_color)
+            }
+            ("background", Value::Integer(index)) => {
+                colors.background = index.as_u64().and_then(cterm_color)
+            }
+            _ => {}
+        }
+    }
+
+    Ok(colors)
+}
+
+// Neovim sends -1 for default colors which aren't set, those are left as None.
+fn parse_default_color(color: Value) -> Result<Option<Color4f>> {
+    let packed_color = parse_i64(color)?;
+    if packed_color < 0 {
+        Ok(None)
+    } else {
+        Ok(Some(unpack_color(packed_color as u64)))
+    }
+}
+
 fn parse_default_colors(default_colors_arguments: Vec<Value>) -> Result<RedrawEvent> {
-    let [foreground, background, special, _term_foreground, _term_background] =
+    let [foreground, background, special, term_foreground, term_background] =
         extract_values(default_colors_arguments)?;
 
     Ok(RedrawEvent::DefaultColorsSet {
         colors: Colors {
-            foreground: Some(unpack_color(parse_u64(foreground)?)),
-            background: Some(unpack_color(parse_u64(background)?)),
-            special: Some(unpack_color(parse_u64(special)?)),
+            foreground: parse_default_color(foreground)?,
+            background: parse_default_color(background)?,
+            special: parse_default_color(special)?,
+        },
+        cterm_colors: Colors {
+            foreground: parse_default_cterm_color(term_foreground)?,
+            background: parse_default_cterm_color(term_background)?,
+            special: None,
         },
     })
 }
@@ -488,12 +665,21 @@ fn parse_style(style_map: Value) -> Result<Style> {
                     style.colors.special = Some(unpack_color(packed_color.as_u64().unwrap()))
                 }
                 ("reverse", Value::Boolean(reverse)) => style.reverse = reverse,
+                ("standout", Value::Boolean(standout)) => style.standout = standout,
+                ("nocombine", Value::Boolean(nocombine)) => style.nocombine = nocombine,
+                ("altfont", Value::Boolean(altfont)) => style.altfont = altfont,
+                ("spell", Value::Boolean(spell)) => style.spell = spell,
+                ("url", Value::String(url)) => style.url = url.into_str(),
                 ("italic", Value::Boolean(italic)) => style.italic = italic,
                 ("bold", Value::Boolean(bold)) => style.bold = bold,
                 ("strikethrough", Value::Boolean(strikethrough)) => {
                     style.strikethrough = strikethrough
                 }
-                ("blend", Value::Integer(blend)) => style.blend = blend.as_u64().unwrap() as u8,
+                ("blend", Value::Integer(blend)) => {
+                    // Blend is a percentage, anything outside of that range would underflow the
+                    // alpha computation when rendering
+                    style.blend = blend.as_u64().unwrap_or(0).min(100) as u8
+                }
 
                 ("underline", Value::Boolean(true)) => {
                     style.underline = Some(UnderlineStyle::Underline)
@@ -522,12 +708,13 @@ fn parse_style(style_map: Value) -> Result<Style> {
 }
 
 fn parse_hl_attr_define(hl_attr_define_arguments: Vec<Value>) -> Result<RedrawEvent> {
-    let [id, attributes, _terminal_attributes, _info] = extract_values(hl_attr_define_arguments)?;
+    let [id, attributes, terminal_attributes, _info] = extract_values(hl_attr_define_arguments)?;
 
     let style = parse_style(attributes)?;
     Ok(RedrawEvent::HighlightAttributesDefine {
         id: parse_u64(id)?,
         style,
+        cterm_colors: parse_cterm_colors(terminal_attributes)?,
     })
 }
 
@@ -562,7 +749,8 @@ fn parse_grid_line_cell(grid_line_cell: Value) -> Result<GridLineCell> {
 }
 
 fn parse_grid_line(grid_line_arguments: Vec<Value>) -> Result<RedrawEvent> {
-    let [grid_id, row, column_start, cells] = extract_values(grid_line_arguments)?;
+    let ([grid_id, row, column_start, cells], [wrap]) =
+        extract_values_with_optional(grid_line_arguments)?;
 
     Ok(RedrawEvent::GridLine {
         grid: parse_u64(grid_id)?,
@@ -572,6 +760,8 @@ fn parse_grid_line(grid_line_arguments: Vec<Value>) -> Result<RedrawEvent> {
             .into_iter()
             .map(parse_grid_line_cell)
             .collect::<Result<Vec<GridLineCell>>>()?,
+        // Only sent by Neovim versions that report whether the line continues on the next row
+        wrap: wrap.map(parse_bool).transpose()?.unwrap_or(false),
     })
 }
 
@@ -695,8 +885,10 @@ fn parse_msg_set_pos(msg_set_pos_arguments: Vec<Value>) -> Result<RedrawEvent> {
 }
 
 fn parse_win_viewport(win_viewport_arguments: Vec<Value>) -> Result<RedrawEvent> {
-    let ([grid, _window, top_line, bottom_line, current_line, current_column], [line_count]) =
-        extract_values_with_optional(win_viewport_arguments)?;
+    let (
+        [grid, _window, top_line, bottom_line, current_line, current_column],
+        [line_count, scroll_delta],
+    ) = extract_values_with_optional(win_viewport_arguments)?;
 
     let line_count = if let Some(line_count) = line_count {
         Some(parse_f64(line_count)?)
@@ -704,6 +896,12 @@ fn parse_win_viewport(win_viewport_arguments: Vec<Value>) -> Result<RedrawEvent>
         None
     };
 
+    let scroll_delta = if let Some(scroll_delta) = scroll_delta {
+        Some(parse_i64(scroll_delta)?)
+    } else {
+        None
+    };
+
     Ok(RedrawEvent::WindowViewport {
         grid: parse_u64(grid)?,
         top_line: parse_f64(top_line)?,
@@ -711,6 +909,7 @@ fn parse_win_viewport(win_viewport_arguments: Vec<Value>) -> Result<RedrawEvent>
         current_line: parse_f64(current_line)?,
         current_column: parse_f64(current_column)?,
         line_count,
+        scroll_delta,
     })
 }
 
@@ -777,6 +976,46 @@ fn parse_cmdline_block_append(cmdline_block_append_arguments: Vec<Value>) -> Res
     })
 }
 
+// Neovim sends -1 when no item is selected.
+fn parse_popupmenu_selected(selected: Value) -> Result<Option<u64>> {
+    Ok(u64::try_from(parse_i64(selected)?).ok())
+}
+
+fn parse_popupmenu_item(item: Value) -> Result<PopupMenuItem> {
+    let [word, kind, menu, info] = extract_values(parse_array(item)?)?;
+
+    Ok(PopupMenuItem {
+        word: parse_string(word)?,
+        kind: parse_string(kind)?,
+        menu: parse_string(menu)?,
+        info: parse_string(info)?,
+    })
+}
+
+fn parse_popupmenu_show(popupmenu_show_arguments: Vec<Value>) -> Result<RedrawEvent> {
+    let [items, selected, row, column, grid] = extract_values(popupmenu_show_arguments)?;
+
+    Ok(RedrawEvent::PopupMenuShow {
+        items: parse_array(items)?
+            .into_iter()
+            .map(parse_popupmenu_item)
+            .collect::<Result<_>>()?,
+        selected: parse_popupmenu_selected(selected)?,
+        row: parse_u64(row)?,
+        column: parse_u64(column)?,
+        // The grid is -1 when the menu completes the command line of ext_cmdline.
+        grid: u64::try_from(parse_i64(grid)?).ok(),
+    })
+}
+
+fn parse_popupmenu_select(popupmenu_select_arguments: Vec<Value>) -> Result<RedrawEvent> {
+    let [selected] = extract_values(popupmenu_select_arguments)?;
+
+    Ok(RedrawEvent::PopupMenuSelect {
+        selected: parse_popupmenu_selected(selected)?,
+    })
+}
+
 fn parse_msg_show(msg_show_arguments: Vec<Value>) -> Result<RedrawEvent> {
     let [kind, content, replace_last] = extract_values(msg_show_arguments)?;
 
@@ -845,7 +1084,7 @@ pub fn parse_redraw_event(event_value: Value) -> Result<Vec<RedrawEvent>> {
         let event_parameters = parse_array(event)?;
         let possible_parsed_event = match event_name.as_str() {
             "set_title" => Some(parse_set_title(event_parameters)?),
-            "set_icon" => None, // Ignore set icon for now
+            "set_icon" => Some(parse_set_icon(event_parameters)?),
             "mode_info_set" => Some(parse_mode_info_set(event_parameters)?),
             "option_set" => Some(parse_option_set(event_parameters)?),
             "mode_change" => Some(parse_mode_change(event_parameters)?),
@@ -853,6 +1092,9 @@ pub fn parse_redraw_event(event_value: Value) -> Result<Vec<RedrawEvent>> {
             "mouse_off" => Some(RedrawEvent::MouseOff),
             "busy_start" => Some(RedrawEvent::BusyStart),
             "busy_stop" => Some(RedrawEvent::BusyStop),
+            "suspend" => Some(RedrawEvent::Suspend),
+            "bell" => Some(RedrawEvent::Bell),
+            "visual_bell" => Some(RedrawEvent::VisualBell),
             "flush" => Some(RedrawEvent::Flush),
             "grid_resize" => Some(parse_grid_resize(event_parameters)?),
             "default_colors_set" => Some(parse_default_colors(event_parameters)?),
@@ -882,6 +1124,10 @@ pub fn parse_redraw_event(event_value: Value) -> Result<Vec<RedrawEvent>> {
             "msg_showcmd" => Some(parse_msg_showcmd(event_parameters)?),
             "msg_ruler" => Some(parse_msg_ruler(event_parameters)?),
             "msg_history_show" => Some(parse_msg_history_show(event_parameters)?),
+            "popupmenu_show" => Some(parse_popupmenu_show(event_parameters)?),
+            "popupmenu_select" => Some(parse_popupmenu_select(event_parameters)?),
+            "popupmenu_hide" => Some(RedrawEvent::PopupMenuHide),
+            "tabline_update" => Some(parse_tabline_update(event_parameters)?),
             _ => None,
         };
 
@@ -892,3 +1138,129 @@ pub fn parse_redraw_event(event_value: Value) -> Result<Vec<RedrawEvent>> {
 
     Ok(parsed_events)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORANGE: u64 = 0xff8000;
+
+    fn orange() -> Color4f {
+        Color4f::new(1.0, 128.0 / 255.0, 0.0, 1.0)
+    }
+
+    #[test]
+    fn packed_rgb_colors_are_unpacked_into_skia_colors() {
+        assert_eq!(unpack_color(ORANGE), orange());
+        assert_eq!(unpack_color(0), Color4f::new(0.0, 0.0, 0.0, 1.0));
+        assert_eq!(unpack_color(0xffffff), Color4f::new(1.0, 1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn default_colors_use_the_rgb_values_and_treat_negative_ones_as_unset() {
+        let event = parse_default_colors(vec![
+            Value::from(ORANGE),
+            Value::from(0x000000),
+            Value::from(-1),
+            Value::from(3),
+            Value::from(0),
+        ])
+        .unwrap();
+
+        if let RedrawEvent::DefaultColorsSet {
+            colors,
+            cterm_colors,
+        } = event
+        {
+            assert_eq!(
+                colors,
+                Colors::new(Some(orange()), Some(Color4f::new(0.0, 0.0, 0.0, 1.0)), None)
+            );
+            assert_eq!(cterm_colors, Colors::new(cterm_color(2), None, None));
+        } else {
+            panic!("Expected DefaultColorsSet, got {:?}", event);
+        }
+    }
+
+    #[test]
+    fn cterm_palette_indices_are_converted_to_xterm_colors() {
+        assert_eq!(cterm_color(1), Some(unpack_color(0xcd0000)));
+        assert_eq!(cterm_color(15), Some(unpack_color(0xffffff)));
+        assert_eq!(cterm_color(16), Some(unpack_color(0x000000)));
+        assert_eq!(cterm_color(196), Some(unpack_color(0xff0000)));
+        assert_eq!(cterm_color(208), Some(unpack_color(0xff8700)));
+        assert_eq!(cterm_color(232), Some(unpack_color(0x080808)));
+        assert_eq!(cterm_color(255), Some(unpack_color(0xeeeeee)));
+        assert_eq!(cterm_color(256), None);
+    }
+
+    #[test]
+    fn cterm_colors_are_parsed_next_to_the_rgb_ones() {
+        let rgb_attributes = Value::Map(vec![(Value::from("foreground"), Value::from(ORANGE))]);
+        let cterm_attributes = Value::Map(vec![
+            (Value::from("foreground"), Value::from(1)),
+            (Value::from("background"), Value::from(196)),
+        ]);
+        let event = parse_hl_attr_define(vec![
+            Value::from(7),
+            rgb_attributes,
+            cterm_attributes,
+            Value::Array(Vec::new()),
+        ])
+        .unwrap();
+
+        if let RedrawEvent::HighlightAttributesDefine {
+            style,
+            cterm_colors,
+            ..
+        } = event
+        {
+            assert_eq!(style.colors, Colors::new(Some(orange()), None, None));
+            assert_eq!(
+                cterm_colors,
+                Colors::new(cterm_color(1), cterm_color(196), None)
+            );
+        } else {
+            panic!("Expected HighlightAttributesDefine, got {:?}", event);
+        }
+
+        let event = parse_default_colors(vec![
+            Value::from(-1),
+            Value::from(-1),
+            Value::from(-1),
+            Value::from(8),
+            Value::from(0),
+        ])
+        .unwrap();
+
+        if let RedrawEvent::DefaultColorsSet { cterm_colors, .. } = event {
+            assert_eq!(cterm_colors, Colors::new(cterm_color(7), None, None));
+        } else {
+            panic!("Expected DefaultColorsSet, got {:?}", event);
+        }
+    }
+
+    #[test]
+    fn highlight_colors_come_from_the_rgb_attributes() {
+        let rgb_attributes = Value::Map(vec![
+            (Value::from("foreground"), Value::from(ORANGE)),
+            (Value::from("bold"), Value::from(true)),
+        ]);
+        let cterm_attributes = Value::Map(vec![(Value::from("foreground"), Value::from(1))]);
+        let event = parse_hl_attr_define(vec![
+            Value::from(7),
+            rgb_attributes,
+            cterm_attributes,
+            Value::Array(Vec::new()),
+        ])
+        .unwrap();
+
+        if let RedrawEvent::HighlightAttributesDefine { id, style, .. } = event {
+            assert_eq!(id, 7);
+            assert_eq!(style.colors, Colors::new(Some(orange()), None, None));
+            assert!(style.bold);
+        } else {
+            panic!("Expected HighlightAttributesDefine, got {:?}", event);
+        }
+    }
+}
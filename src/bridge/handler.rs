@@ -4,6 +4,8 @@ use nvim_rs::{Handler, Neovim};
 use rmpv::Value;
 
 use crate::bridge::clipboard::{get_clipboard_contents, set_clipboard_contents};
+#[cfg(feature = "event-recording")]
+use crate::bridge::event_recorder;
 #[cfg(windows)]
 use crate::bridge::ui_commands::{ParallelCommand, UiCommand};
 use crate::{
@@ -72,6 +74,8 @@ impl Handler for NeovimHandler {
                         .unwrap_or_explained_panic("Could not parse event from neovim");
 
                     for parsed_event in parsed_events {
+                        #[cfg(feature = "event-recording")]
+                        event_recorder::record_event(&parsed_event);
                         EVENT_AGGREGATOR.send(EditorCommand::NeovimRedrawEvent(parsed_event));
                     }
                 }
@@ -1,6 +1,8 @@
 mod clipboard;
 mod command;
 pub mod create;
+#[cfg(feature = "event-recording")]
+mod event_recorder;
 mod events;
 mod handler;
 mod setup;
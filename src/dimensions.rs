@@ -99,6 +99,23 @@ impl Div for Dimensions {
     }
 }
 
+impl Dimensions {
+    /// Splits a size in pixels into the whole cells that fit in it and the pixels left over, so
+    /// that the window can be snapped to a multiple of the cell size or the grid centered in it. A
+    /// size smaller than one cell still holds a single cell, with nothing left over.
+    pub fn fit_cells(self, cell: Dimensions) -> (Dimensions, Dimensions) {
+        let cell = Dimensions::from((cell.width.max(1), cell.height.max(1)));
+        let cells = self / cell;
+        let cells = Dimensions::from((cells.width.max(1), cells.height.max(1)));
+        let used = cells * cell;
+        let leftover = Dimensions::from((
+            self.width.saturating_sub(used.width),
+            self.height.saturating_sub(used.height),
+        ));
+        (cells, leftover)
+    }
+}
+
 impl Mul<Dimensions> for (u64, u64) {
     type Output = Self;
 
@@ -107,3 +124,35 @@ impl Mul<Dimensions> for (u64, u64) {
         (x * other.width, y * other.height)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_cells_returns_whole_cells_and_the_leftover_pixels() {
+        let cell = Dimensions::from((10u64, 20u64));
+
+        assert_eq!(
+            Dimensions::from((105u64, 200u64)).fit_cells(cell),
+            (
+                Dimensions::from((10u64, 10u64)),
+                Dimensions::from((5u64, 0u64))
+            )
+        );
+        assert_eq!(
+            Dimensions::from((4u64, 30u64)).fit_cells(cell),
+            (
+                Dimensions::from((1u64, 1u64)),
+                Dimensions::from((0u64, 10u64))
+            )
+        );
+        assert_eq!(
+            Dimensions::from((7u64, 7u64)).fit_cells(Dimensions::from((0u64, 0u64))),
+            (
+                Dimensions::from((7u64, 7u64)),
+                Dimensions::from((0u64, 0u64))
+            )
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use std::{collections::HashMap, sync::Arc};
 
+#[cfg(feature = "event-recording")]
+use serde::{Deserialize, Serialize};
 use skia_safe::Color4f;
 
 use crate::editor::style::{Colors, Style};
@@ -7,6 +9,7 @@ use crate::editor::style::{Colors, Style};
 use super::grid::GridCell;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "event-recording", derive(Serialize, Deserialize))]
 pub enum CursorShape {
     Block,
     Horizontal,
@@ -25,6 +28,7 @@ impl CursorShape {
 }
 
 #[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "event-recording", derive(Serialize, Deserialize))]
 pub struct CursorMode {
     pub shape: Option<CursorShape>,
     pub style_id: Option<u64>,
@@ -32,6 +36,10 @@ pub struct CursorMode {
     pub blinkwait: Option<u64>,
     pub blinkon: Option<u64>,
     pub blinkoff: Option<u64>,
+    pub name: Option<String>,
+    pub short_name: Option<String>,
+    // Neovim's mouse shape id for the mode, so that the pointer can change along with the cursor
+    pub mouse_shape: Option<u64>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -66,18 +74,26 @@ impl Cursor {
         }
     }
 
+    // When the cursor highlight doesn't specify a color, the cell under the cursor is inverted.
+    // The cell's own style is used so that the cursor is still visible over reversed text.
     pub fn foreground(&self, default_colors: &Colors) -> Color4f {
         self.style
             .as_ref()
             .and_then(|s| s.colors.foreground)
-            .unwrap_or_else(|| default_colors.background.unwrap())
+            .unwrap_or_else(|| match &self.grid_cell.1 {
+                Some(cell_style) => cell_style.background(default_colors),
+                None => default_colors.background.unwrap(),
+            })
     }
 
     pub fn background(&self, default_colors: &Colors) -> Color4f {
         self.style
             .as_ref()
             .and_then(|s| s.colors.background)
-            .unwrap_or_else(|| default_colors.foreground.unwrap())
+            .unwrap_or_else(|| match &self.grid_cell.1 {
+                Some(cell_style) => cell_style.foreground(default_colors),
+                None => default_colors.foreground.unwrap(),
+            })
     }
 
     pub fn alpha(&self) -> u8 {
@@ -96,6 +112,7 @@ impl Cursor {
             blinkwait,
             blinkon,
             blinkoff,
+            ..
         } = cursor_mode;
 
         if let Some(shape) = shape {
@@ -195,6 +212,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_colors_invert_reversed_cell() {
+        let mut cursor = Cursor::new();
+        let mut cell_style = Style::new(COLORS);
+        cell_style.reverse = true;
+        cursor.grid_cell = ("a".to_string(), Some(Arc::new(cell_style)));
+
+        assert_eq!(
+            cursor.foreground(&DEFAULT_COLORS),
+            COLORS.foreground.unwrap()
+        );
+        assert_eq!(
+            cursor.background(&DEFAULT_COLORS),
+            COLORS.background.unwrap()
+        );
+
+        cursor.style = Some(Arc::new(Style::new(COLORS)));
+        assert_eq!(
+            cursor.background(&DEFAULT_COLORS),
+            COLORS.background.unwrap()
+        );
+    }
+
     #[test]
     fn test_change_mode() {
         let cursor_mode = CursorMode {
@@ -204,6 +244,9 @@ mod tests {
             blinkwait: Some(1),
             blinkon: Some(1),
             blinkoff: Some(1),
+            name: None,
+            short_name: None,
+            mouse_shape: None,
         };
         let mut styles = HashMap::new();
         styles.insert(1, Arc::new(Style::new(COLORS)));
@@ -225,6 +268,9 @@ mod tests {
             blinkwait: None,
             blinkon: None,
             blinkoff: None,
+            name: None,
+            short_name: None,
+            mouse_shape: None,
         };
         cursor.change_mode(&cursor_mode_with_none, &styles);
         assert_eq!(cursor.shape, CursorShape::Horizontal);
@@ -21,8 +21,12 @@ impl DrawCommandBatcher {
         self.window_draw_command_sender.send(draw_command)
     }
 
+    /// Drains the queued draw commands without sending them to the renderer.
+    pub fn take_batch(&self) -> Vec<DrawCommand> {
+        self.window_draw_command_receiver.try_iter().collect()
+    }
+
     pub fn send_batch(&self) {
-        let batch: Vec<DrawCommand> = self.window_draw_command_receiver.try_iter().collect();
-        EVENT_AGGREGATOR.send(batch);
+        EVENT_AGGREGATOR.send(self.take_batch());
     }
 }
@@ -1,5 +1,7 @@
 use std::sync::Arc;
 
+use log::warn;
+
 use crate::editor::style::Style;
 
 pub type GridCell = (String, Option<Arc<Style>>);
@@ -11,32 +13,59 @@ macro_rules! default_cell {
     };
 }
 
+// Upper bounds for the grid size, so that a bogus size can't allocate an enormous buffer. They
+// leave room for a tiny font on an 8K display while capping a grid at about 2M cells.
+const MAX_GRID_WIDTH: u64 = 2048;
+const MAX_GRID_HEIGHT: u64 = 1024;
+
+fn clamp_size((width, height): (u64, u64)) -> (u64, u64) {
+    let clamped_size = (
+        width.clamp(1, MAX_GRID_WIDTH),
+        height.clamp(1, MAX_GRID_HEIGHT),
+    );
+    if clamped_size != (width, height) {
+        warn!(
+            "Grid size {}x{} clamped to {}x{}",
+            width, height, clamped_size.0, clamped_size.1
+        );
+    }
+    clamped_size
+}
+
 pub struct CharacterGrid {
     pub width: u64,
     pub height: u64,
 
     characters: Vec<GridCell>,
+    // Whether each cell was cleared and not drawn since, so that gaps Neovim never filled can be
+    // told apart from the spaces it drew
+    blank_cells: Vec<bool>,
 }
 
 impl CharacterGrid {
     pub fn new(size: (u64, u64)) -> CharacterGrid {
-        let (width, height) = size;
+        let (width, height) = clamp_size(size);
         let cell_count = (width * height) as usize;
         CharacterGrid {
             characters: vec![default_cell!(); cell_count],
+            blank_cells: vec![true; cell_count],
             width,
             height,
         }
     }
 
-    pub fn resize(&mut self, (width, height): (u64, u64)) {
+    pub fn resize(&mut self, size: (u64, u64)) {
+        let (width, height) = clamp_size(size);
         let new_cell_count = (width * height) as usize;
         let mut new_characters = vec![default_cell!(); new_cell_count];
+        let mut new_blank_cells = vec![true; new_cell_count];
 
         for x in 0..self.width.min(width) {
             for y in 0..self.height.min(height) {
-                if let Some(existing_cell) = self.get_cell(x, y) {
-                    new_characters[(x + y * width) as usize] = existing_cell.clone();
+                if let Some(index) = self.cell_index(x, y) {
+                    let new_index = (x + y * width) as usize;
+                    new_characters[new_index] = self.characters[index].clone();
+                    new_blank_cells[new_index] = self.blank_cells[index];
                 }
             }
         }
@@ -44,10 +73,41 @@ impl CharacterGrid {
         self.width = width;
         self.height = height;
         self.characters = new_characters;
+        self.blank_cells = new_blank_cells;
     }
 
     pub fn clear(&mut self) {
         self.set_all_characters(default_cell!());
+        self.blank_cells.fill(true);
+    }
+
+    /// Clears the cells from top to bottom and left to right, excluding bottom and right. The
+    /// parts of the region outside of the grid are ignored.
+    pub fn clear_region(&mut self, top: u64, bottom: u64, left: u64, right: u64) {
+        for y in top..bottom.min(self.height) {
+            for x in left..right.min(self.width) {
+                if let Some(index) = self.cell_index(x, y) {
+                    self.characters[index] = default_cell!();
+                    self.blank_cells[index] = true;
+                }
+            }
+        }
+    }
+
+    /// Copies a cell to another position, keeping whether it is blank.
+    pub fn copy_cell(&mut self, (x, y): (u64, u64), (dest_x, dest_y): (u64, u64)) {
+        if let (Some(index), Some(dest_index)) =
+            (self.cell_index(x, y), self.cell_index(dest_x, dest_y))
+        {
+            self.characters[dest_index] = self.characters[index].clone();
+            self.blank_cells[dest_index] = self.blank_cells[index];
+        }
+    }
+
+    /// Returns whether the cell was cleared and hasn't been drawn to since.
+    pub fn is_blank(&self, x: u64, y: u64) -> bool {
+        self.cell_index(x, y)
+            .map_or(false, |index| self.blank_cells[index])
     }
 
     fn cell_index(&self, x: u64, y: u64) -> Option<usize> {
@@ -62,15 +122,20 @@ impl CharacterGrid {
         self.cell_index(x, y).map(|idx| &self.characters[idx])
     }
 
+    /// The cell is no longer blank once it is borrowed for writing.
     pub fn get_cell_mut(&mut self, x: u64, y: u64) -> Option<&mut GridCell> {
-        self.cell_index(x, y)
-            .map(move |idx| &mut self.characters[idx])
+        self.cell_index(x, y).map(move |idx| {
+            self.blank_cells[idx] = false;
+            &mut self.characters[idx]
+        })
     }
 
     pub fn set_all_characters(&mut self, value: GridCell) {
+        let cell_count = (self.width * self.height) as usize;
         self.characters.clear();
-        self.characters
-            .resize_with((self.width * self.height) as usize, || value.clone());
+        self.characters.resize_with(cell_count, || value.clone());
+        self.blank_cells.clear();
+        self.blank_cells.resize(cell_count, false);
     }
 
     pub fn row(&self, row_index: u64) -> Option<&[GridCell]> {
@@ -224,6 +289,86 @@ mod tests {
         );
     }
 
+    #[test]
+    fn clear_region_only_clears_inside_the_region() {
+        let mut character_grid = CharacterGrid::new((3, 3));
+        character_grid.set_all_characters(("x".to_string(), None));
+
+        // RUN FUNCTION
+        character_grid.clear_region(1, 10, 1, 2);
+
+        let characters: String = character_grid
+            .characters
+            .iter()
+            .map(|(character, _)| character.as_str())
+            .collect();
+        assert_eq!(characters, "xxxx xx x");
+    }
+
+    #[test]
+    fn cells_are_blank_until_drawn_to() {
+        let mut character_grid = CharacterGrid::new((3, 2));
+        assert!(character_grid.is_blank(1, 1));
+
+        *character_grid.get_cell_mut(1, 1).unwrap() = (" ".to_string(), None);
+        assert!(!character_grid.is_blank(1, 1));
+
+        // Copies keep the state of the copied cell
+        character_grid.copy_cell((1, 1), (0, 0));
+        character_grid.copy_cell((2, 1), (1, 1));
+        assert!(!character_grid.is_blank(0, 0));
+        assert!(character_grid.is_blank(1, 1));
+
+        character_grid.resize((4, 2));
+        assert!(!character_grid.is_blank(0, 0));
+        assert!(character_grid.is_blank(3, 0));
+
+        character_grid.clear_region(0, 1, 0, 1);
+        assert!(character_grid.is_blank(0, 0));
+
+        character_grid.set_all_characters(default_cell!());
+        assert!(!character_grid.is_blank(2, 1));
+        character_grid.clear();
+        assert!(character_grid.is_blank(2, 1));
+        assert!(!character_grid.is_blank(10, 10));
+    }
+
+    #[test]
+    fn clear_reuses_the_existing_allocation() {
+        let context = Context::new();
+        let mut character_grid = CharacterGrid::new(context.size);
+        let capacity = character_grid.characters.capacity();
+        let buffer = character_grid.characters.as_ptr();
+
+        // RUN FUNCTION
+        character_grid.clear();
+        character_grid.clear();
+
+        assert_eq!(character_grid.characters.capacity(), capacity);
+        assert_eq!(character_grid.characters.as_ptr(), buffer);
+    }
+
+    #[test]
+    fn resize_clamps_zero_and_huge_sizes() {
+        let mut character_grid = CharacterGrid::new((0, 0));
+        assert_eq!((character_grid.width, character_grid.height), (1, 1));
+
+        // RUN FUNCTION
+        character_grid.resize((u64::MAX, 0));
+
+        assert_eq!(
+            (character_grid.width, character_grid.height),
+            (MAX_GRID_WIDTH, 1)
+        );
+        assert_eq!(character_grid.characters.len(), MAX_GRID_WIDTH as usize);
+
+        character_grid.resize((0, u64::MAX));
+        assert_eq!(
+            (character_grid.width, character_grid.height),
+            (1, MAX_GRID_HEIGHT)
+        );
+    }
+
     #[test]
     fn resize_clears_and_resizes_grid() {
         let context = Context::new();
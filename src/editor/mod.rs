@@ -7,9 +7,14 @@ mod window;
 use std::{collections::HashMap, sync::Arc, thread};
 
 use log::{error, trace};
+use rmpv::Value;
+use unicode_normalization::UnicodeNormalization;
 
 use crate::{
-    bridge::{GuiOption, RedrawEvent, WindowAnchor},
+    bridge::{
+        GridLineCell, GuiOption, MessageKind, PopupMenuItem, RedrawEvent, SerialCommand,
+        StyledContent, TablineEntries, UiCommand, WindowAnchor,
+    },
     event_aggregator::EVENT_AGGREGATOR,
     redraw_scheduler::REDRAW_SCHEDULER,
     renderer::DrawCommand,
@@ -23,6 +28,8 @@ pub use style::{Colors, Style, UnderlineStyle};
 pub use window::*;
 
 const MODE_CMDLINE: u64 = 4;
+const MIN_FONT_SIZE: f32 = 1.0;
+const MAX_FONT_SIZE: f32 = 72.0;
 
 #[derive(Clone, Debug)]
 pub struct AnchorInfo {
@@ -33,6 +40,31 @@ pub struct AnchorInfo {
     pub sort_order: u64,
 }
 
+/// The state of a command line sent through ext_cmdline. `position` is the byte offset of the
+/// cursor in the content, and `special_character` is the character shown under the cursor after
+/// keys such as <C-v> until the next key is typed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandLine {
+    pub content: StyledContent,
+    pub position: u64,
+    pub first_character: String,
+    pub prompt: String,
+    pub indent: u64,
+    pub level: u64,
+    pub special_character: Option<(String, bool)>,
+}
+
+/// The completion menu sent through ext_popupmenu. It is anchored at `row` and `column` of
+/// `grid`, or at the command line when `grid` is None.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PopupMenu {
+    pub items: Vec<PopupMenuItem>,
+    pub selected: Option<u64>,
+    pub row: u64,
+    pub column: u64,
+    pub grid: Option<u64>,
+}
+
 impl WindowAnchor {
     fn modified_top_left(
         &self,
@@ -54,6 +86,20 @@ impl WindowAnchor {
 pub enum EditorCommand {
     NeovimRedrawEvent(RedrawEvent),
     RedrawScreen,
+    /// Sent by the keyboard handler on Ctrl+= and Ctrl+- to zoom the font in or out, when
+    /// g:neovide_zoom_keys is set. Holds the keybinding to send to Neovim instead when there is no
+    /// size to zoom.
+    AdjustFontSize {
+        delta: f32,
+        keybinding: String,
+    },
+    /// Sent by the window when g:neovide_normalize_text changes.
+    SetNormalizeText(bool),
+    /// Sent by the platform input method handler when a composition starts or ends, so that the
+    /// cursor doesn't cover the preedit text. The glutin version used here only reports the
+    /// committed text, so the platform handler has nothing to send this from yet.
+    #[allow(dead_code)]
+    ImeComposition(bool),
 }
 
 pub struct Editor {
@@ -63,6 +109,40 @@ pub struct Editor {
     pub mode_list: Vec<CursorMode>,
     pub draw_command_batcher: Arc<DrawCommandBatcher>,
     pub current_mode_index: Option<u64>,
+    /// The icon title sent by set_icon and the tab pages and buffers from tabline_update. These
+    /// are only tracked for now so that a GUI titlebar or tabline can be built on top of them.
+    #[allow(dead_code)]
+    pub icon: String,
+    #[allow(dead_code)]
+    pub current_tab: Option<Value>,
+    #[allow(dead_code)]
+    pub tabs: TablineEntries,
+    /// Only sent by Neovim versions that list the buffers in tabline_update.
+    #[allow(dead_code)]
+    pub current_buffer: Option<Value>,
+    #[allow(dead_code)]
+    pub buffers: TablineEntries,
+    /// Messages sent through ext_messages, kept apart from the grids so that a renderer can place
+    /// them. Each entry keeps its kind and highlighted chunks.
+    #[allow(dead_code)]
+    pub messages: Vec<(MessageKind, StyledContent)>,
+    #[allow(dead_code)]
+    pub message_history: Vec<(MessageKind, StyledContent)>,
+    /// The command line and the lines above it while typing a multi-line command such as a
+    /// function definition, tracked from ext_cmdline so that a GUI command line can render them.
+    #[allow(dead_code)]
+    pub command_line: Option<CommandLine>,
+    #[allow(dead_code)]
+    pub command_line_block: Vec<StyledContent>,
+    #[allow(dead_code)]
+    pub popup_menu: Option<PopupMenu>,
+    guifont: Option<String>,
+    /// Cleared when Neovim suspends so that no frames are queued until it redraws again.
+    pub active: bool,
+    normalize_text: bool,
+    ime_composing: bool,
+    /// Neovide turns 'termguicolors' on at startup, the option_set events keep this in sync.
+    term_gui_colors: bool,
 }
 
 impl Editor {
@@ -74,15 +154,57 @@ impl Editor {
             mode_list: Vec::new(),
             draw_command_batcher: Arc::new(DrawCommandBatcher::new()),
             current_mode_index: None,
+            icon: String::new(),
+            current_tab: None,
+            tabs: Vec::new(),
+            current_buffer: None,
+            buffers: Vec::new(),
+            messages: Vec::new(),
+            message_history: Vec::new(),
+            command_line: None,
+            command_line_block: Vec::new(),
+            popup_menu: None,
+            guifont: None,
+            active: true,
+            normalize_text: false,
+            ime_composing: false,
+            term_gui_colors: true,
         }
     }
 
     pub fn handle_editor_command(&mut self, command: EditorCommand) {
+        // Any redraw after a suspend means Neovim is back, so frames are queued again.
+        if let EditorCommand::NeovimRedrawEvent(event) = &command {
+            if !matches!(event, RedrawEvent::Suspend | RedrawEvent::Flush) {
+                self.active = true;
+            }
+        }
+
         match command {
             EditorCommand::NeovimRedrawEvent(event) => match event {
                 RedrawEvent::SetTitle { title } => {
                     EVENT_AGGREGATOR.send(WindowCommand::TitleChanged(title));
                 }
+                RedrawEvent::SetIcon { icon } => {
+                    trace!("Icon title set to {}", icon);
+                    self.icon = icon;
+                }
+                RedrawEvent::TablineUpdate {
+                    current_tab,
+                    tabs,
+                    current_buffer,
+                    buffers,
+                } => {
+                    trace!(
+                        "Tabline updated with {} tabs and {} buffers",
+                        tabs.len(),
+                        buffers.len()
+                    );
+                    self.current_tab = Some(current_tab);
+                    self.tabs = tabs;
+                    self.current_buffer = current_buffer;
+                    self.buffers = buffers;
+                }
                 RedrawEvent::ModeInfoSet { cursor_modes } => {
                     self.mode_list = cursor_modes;
                     if let Some(current_mode_i) = self.current_mode_index {
@@ -118,21 +240,29 @@ impl Editor {
                     self.cursor.enabled = true;
                 }
                 RedrawEvent::Flush => {
-                    trace!("Image flushed");
+                    trace_sampled!("Image flushed");
                     self.send_cursor_info();
                     self.draw_command_batcher.send_batch();
-                    REDRAW_SCHEDULER.queue_next_frame();
+                    if self.active {
+                        REDRAW_SCHEDULER.queue_next_frame();
+                    }
                 }
-                RedrawEvent::DefaultColorsSet { colors } => {
-                    self.draw_command_batcher
-                        .queue(DrawCommand::DefaultStyleChanged(Style::new(colors)))
-                        .ok();
-                    self.redraw_screen();
+                RedrawEvent::DefaultColorsSet {
+                    colors,
+                    cterm_colors,
+                } => {
+                    self.set_default_colors(self.resolve_colors(colors, &cterm_colors));
                     self.draw_command_batcher.send_batch();
                     REDRAW_SCHEDULER.queue_next_frame();
                 }
-                RedrawEvent::HighlightAttributesDefine { id, style } => {
+                RedrawEvent::HighlightAttributesDefine {
+                    id,
+                    mut style,
+                    cterm_colors,
+                } => {
+                    style.colors = self.resolve_colors(style.colors, &cterm_colors);
                     self.defined_styles.insert(id, Arc::new(style));
+                    self.update_cursor_style(id);
                 }
                 RedrawEvent::CursorGoto {
                     grid,
@@ -151,11 +281,20 @@ impl Editor {
                     row,
                     column_start,
                     cells,
+                    wrap,
                 } => {
+                    let cells = if self.normalize_text {
+                        normalize_cells(cells)
+                    } else {
+                        cells
+                    };
                     let defined_styles = &self.defined_styles;
                     let window = self.windows.get_mut(&grid);
                     if let Some(window) = window {
                         window.draw_grid_line(row, column_start, cells, defined_styles);
+                        window.set_row_wrap(row, wrap);
+                    } else {
+                        trace!("grid_line received for uninitialized grid {}", grid);
                     }
                 }
                 RedrawEvent::Clear { grid } => {
@@ -177,6 +316,8 @@ impl Editor {
                     let window = self.windows.get_mut(&grid);
                     if let Some(window) = window {
                         window.scroll_region(top, bottom, left, right, rows, columns);
+                    } else {
+                        trace!("grid_scroll received for uninitialized grid {}", grid);
                     }
                 }
                 RedrawEvent::WindowPosition {
@@ -203,7 +344,7 @@ impl Editor {
                     sort_order,
                 ),
                 RedrawEvent::WindowHide { grid } => {
-                    let window = self.windows.get(&grid);
+                    let window = self.windows.get_mut(&grid);
                     if let Some(window) = window {
                         window.hide();
                     }
@@ -218,12 +359,145 @@ impl Editor {
                     bottom_line,
                     ..
                 } => self.send_updated_viewport(grid, top_line, bottom_line),
+                RedrawEvent::MessageShow {
+                    kind,
+                    content,
+                    replace_last,
+                } => {
+                    if replace_last {
+                        self.messages.pop();
+                    }
+                    self.messages.push((kind, content));
+                }
+                RedrawEvent::MessageClear => self.messages.clear(),
+                RedrawEvent::MessageHistoryShow { entries } => self.message_history = entries,
+                RedrawEvent::CommandLineShow {
+                    content,
+                    position,
+                    first_character,
+                    prompt,
+                    indent,
+                    level,
+                } => {
+                    self.command_line = Some(CommandLine {
+                        content,
+                        position,
+                        first_character,
+                        prompt,
+                        indent,
+                        level,
+                        special_character: None,
+                    })
+                }
+                RedrawEvent::CommandLinePosition { position, level } => {
+                    if let Some(command_line) = self.command_line.as_mut() {
+                        if command_line.level == level {
+                            command_line.position = position;
+                            command_line.special_character = None;
+                        }
+                    }
+                }
+                RedrawEvent::CommandLineSpecialCharacter {
+                    character,
+                    shift,
+                    level,
+                } => {
+                    if let Some(command_line) = self.command_line.as_mut() {
+                        if command_line.level == level {
+                            command_line.special_character = Some((character, shift));
+                        }
+                    }
+                }
+                RedrawEvent::CommandLineHide => self.command_line = None,
+                RedrawEvent::CommandLineBlockShow { lines } => self.command_line_block = lines,
+                RedrawEvent::CommandLineBlockAppend { line } => self.command_line_block.push(line),
+                RedrawEvent::CommandLineBlockHide => self.command_line_block.clear(),
+                RedrawEvent::PopupMenuShow {
+                    items,
+                    selected,
+                    row,
+                    column,
+                    grid,
+                } => {
+                    self.popup_menu = Some(PopupMenu {
+                        items,
+                        selected,
+                        row,
+                        column,
+                        grid,
+                    })
+                }
+                RedrawEvent::PopupMenuSelect { selected } => {
+                    if let Some(popup_menu) = self.popup_menu.as_mut() {
+                        popup_menu.selected = selected;
+                    }
+                }
+                RedrawEvent::PopupMenuHide => self.popup_menu = None,
+                RedrawEvent::Suspend => {
+                    trace!("Neovim suspended");
+                    self.active = false;
+                }
+                RedrawEvent::Bell => trace!("Bell rung"),
+                RedrawEvent::VisualBell => {
+                    self.draw_command_batcher
+                        .queue(DrawCommand::VisualBell)
+                        .ok();
+                }
                 _ => {}
             },
             EditorCommand::RedrawScreen => self.redraw_screen(),
+            EditorCommand::AdjustFontSize { delta, keybinding } => {
+                if self.adjust_font_size(delta) {
+                    self.draw_command_batcher.send_batch();
+                    REDRAW_SCHEDULER.queue_next_frame();
+                } else {
+                    EVENT_AGGREGATOR.send(UiCommand::Serial(SerialCommand::Keyboard(keybinding)));
+                }
+            }
+            EditorCommand::SetNormalizeText(normalize_text) => {
+                self.set_normalize_text(normalize_text)
+            }
+            EditorCommand::ImeComposition(composing) => {
+                if self.set_ime_composing(composing) {
+                    self.draw_command_batcher.send_batch();
+                    REDRAW_SCHEDULER.queue_next_frame();
+                }
+            }
         };
     }
 
+    /// Composes grid text into NFC before storing it. Neovim only sends its own ui options through
+    /// option_set, so this is toggled from the gui side instead. Only lines drawn afterwards are
+    /// composed.
+    pub fn set_normalize_text(&mut self, normalize_text: bool) {
+        self.normalize_text = normalize_text;
+    }
+
+    /// Returns the id of the grid the cursor is in along with its (column, row) in that grid.
+    #[cfg(test)]
+    pub fn cursor_position(&self) -> (u64, (u64, u64)) {
+        (self.cursor.parent_window_id, self.cursor.grid_position)
+    }
+
+    #[cfg(test)]
+    pub fn cursor_shape(&self) -> &CursorShape {
+        &self.cursor.shape
+    }
+
+    #[cfg(test)]
+    pub fn cursor_enabled(&self) -> bool {
+        self.cursor.enabled
+    }
+
+    /// Replays a sequence of redraw events, for example ones recorded with the event-recording
+    /// feature, as if they had been sent by Neovim.
+    #[cfg(test)]
+    pub fn apply_events(&mut self, events: &[RedrawEvent]) {
+        for event in events {
+            self.handle_editor_command(EditorCommand::NeovimRedrawEvent(event.clone()));
+        }
+    }
+
     fn close_window(&mut self, grid: u64) {
         if let Some(window) = self.windows.remove(&grid) {
             window.close();
@@ -312,11 +586,11 @@ impl Editor {
     }
 
     fn set_message_position(&mut self, grid: u64, grid_top: u64) {
-        let parent_width = self
+        let (parent_width, parent_height) = self
             .windows
             .get(&1)
-            .map(|parent| parent.get_width())
-            .unwrap_or(1);
+            .map(|parent| (parent.get_width(), parent.get_height()))
+            .unwrap_or((1, 1));
 
         let anchor_info = AnchorInfo {
             anchor_grid_id: 1, // Base Grid
@@ -333,6 +607,9 @@ impl Editor {
                 (parent_width, window.get_height()),
                 (0.0, grid_top as f64),
             );
+            // The message grid is as tall as the screen, so the rows pushed below the base grid
+            // are out of view
+            window.set_visible_rows(Some((0, parent_height.saturating_sub(grid_top))));
             window.show();
         } else {
             let new_window = Window::new(
@@ -409,31 +686,101 @@ impl Editor {
 
     fn send_cursor_info(&mut self) {
         let (grid_left, grid_top) = self.cursor.grid_position;
-        if let Some(window) = self.windows.get(&self.cursor.parent_window_id) {
-            let (character, style, double_width) = window.get_cursor_grid_cell(grid_left, grid_top);
+        let window = self.windows.get(&self.cursor.parent_window_id);
+        // The cursor can briefly land on the second half of a double width character, in which
+        // case it is drawn over the whole character instead of half of it.
+        let grid_left = window.map_or(grid_left, |window| {
+            window.character_start(grid_left, grid_top)
+        });
+        let grid_cell = window.and_then(|window| window.get_cursor_grid_cell(grid_left, grid_top));
+
+        if let Some((character, style, double_width)) = grid_cell {
             self.cursor.grid_cell = (character, style);
             self.cursor.double_width = double_width;
         } else {
             self.cursor.double_width = false;
             self.cursor.grid_cell = (" ".to_string(), None);
         }
+
+        let mut cursor = self.cursor.clone();
+        cursor.grid_position = (grid_left, grid_top);
+        // Hidden without touching the busy state, which is restored once the composition ends.
+        cursor.enabled &= !self.ime_composing;
         self.draw_command_batcher
-            .queue(DrawCommand::UpdateCursor(self.cursor.clone()))
+            .queue(DrawCommand::UpdateCursor(cursor))
             .ok();
     }
 
+    // Groups only defined with ctermfg and ctermbg show their palette colors when 'termguicolors'
+    // is off, like they would in a terminal. With it on Neovim ignores them, and so do we.
+    fn resolve_colors(&self, colors: Colors, cterm_colors: &Colors) -> Colors {
+        if self.term_gui_colors {
+            colors
+        } else {
+            colors.with_fallback(cterm_colors)
+        }
+    }
+
     fn set_option(&mut self, gui_option: GuiOption) {
         trace!("Option set {:?}", &gui_option);
-        if let GuiOption::GuiFont(guifont) = gui_option {
-            if guifont == *"*" {
-                EVENT_AGGREGATOR.send(WindowCommand::ListAvailableFonts);
+        match gui_option {
+            GuiOption::GuiFont(guifont) => {
+                if guifont == *"*" {
+                    EVENT_AGGREGATOR.send(WindowCommand::ListAvailableFonts);
+                } else {
+                    self.guifont = Some(guifont.clone());
+                }
+
+                self.draw_command_batcher
+                    .queue(DrawCommand::FontChanged(guifont))
+                    .ok();
+
+                self.redraw_screen();
             }
+            GuiOption::LineSpace(linespace) => {
+                self.draw_command_batcher
+                    .queue(DrawCommand::LineSpaceChanged(linespace))
+                    .ok();
 
-            self.draw_command_batcher
-                .queue(DrawCommand::FontChanged(guifont))
-                .ok();
+                self.redraw_screen();
+            }
+            GuiOption::TermGuiColors(term_gui_colors) => self.term_gui_colors = term_gui_colors,
+            _ => {}
+        }
+    }
 
-            self.redraw_screen();
+    /// Zooms the font by changing the size in the current guifont. The window layer picks up
+    /// the font change and resizes Neovim to fit the new cell size. Returns false and does
+    /// nothing when guifont doesn't specify a size.
+    pub fn adjust_font_size(&mut self, delta: f32) -> bool {
+        match self
+            .guifont
+            .as_ref()
+            .and_then(|guifont| adjust_guifont_size(guifont, delta))
+        {
+            Some(guifont) => {
+                self.set_option(GuiOption::GuiFont(guifont));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the entry of mode_info_set for the current mode.
+    pub fn current_cursor_mode(&self) -> Option<&CursorMode> {
+        self.current_mode_index
+            .and_then(|mode_index| self.mode_list.get(mode_index as usize))
+    }
+
+    // Mode info may reference highlights that haven't been defined yet, or that get redefined
+    // by a colorscheme change, so the cursor style is looked up again whenever they arrive.
+    fn update_cursor_style(&mut self, id: u64) {
+        let current_style_id = self
+            .current_cursor_mode()
+            .and_then(|cursor_mode| cursor_mode.style_id);
+
+        if current_style_id == Some(id) {
+            self.cursor.style = self.defined_styles.get(&id).cloned();
         }
     }
 
@@ -445,11 +792,86 @@ impl Editor {
         }
     }
 
+    /// Returns the text of the given grid one row at a time, for tests that want to assert on
+    /// what is displayed.
+    #[cfg(test)]
+    pub fn to_text_lines(&self, grid: u64, trim_trailing_spaces: bool) -> Option<Vec<String>> {
+        self.windows
+            .get(&grid)
+            .map(|window| window.to_text_lines(trim_trailing_spaces))
+    }
+
+    /// Returns the style Neovim assigned to a cell of the given grid, or None if the cell is
+    /// unstyled or out of bounds.
+    #[cfg(test)]
+    pub fn style_at(&self, grid: u64, x: u64, y: u64) -> Option<Arc<Style>> {
+        self.windows
+            .get(&grid)
+            .and_then(|window| window.style_at(x, y))
+    }
+
+    #[cfg(test)]
+    pub fn char_at(&self, grid: u64, x: u64, y: u64) -> Option<&str> {
+        self.windows
+            .get(&grid)
+            .and_then(|window| window.char_at(x, y))
+    }
+
+    // Returns whether the composition state changed, in which case the cursor was queued again.
+    fn set_ime_composing(&mut self, composing: bool) -> bool {
+        if self.ime_composing == composing {
+            return false;
+        }
+
+        self.ime_composing = composing;
+        self.send_cursor_info();
+        true
+    }
+
     fn redraw_screen(&mut self) {
         for window in self.windows.values() {
             window.redraw();
         }
     }
+
+    // Cells without a highlight are drawn with the default colors, so every row has to be redrawn
+    // for a theme change to show up on text Neovim doesn't send again.
+    fn set_default_colors(&mut self, colors: Colors) {
+        self.draw_command_batcher
+            .queue(DrawCommand::DefaultStyleChanged(Style::new(colors)))
+            .ok();
+        self.redraw_screen();
+    }
+}
+
+// Composes the text of each cell into NFC, so that for example e followed by a combining accent is
+// shaped the same as the precomposed character. Neovim already sends one grapheme per cell and
+// composing never splits or joins graphemes, so the cell count stays the same. The width of the
+// composed text can still differ from what Neovim computed for the decomposed text in rare cases,
+// which is why this is opt in.
+fn normalize_cells(cells: Vec<GridLineCell>) -> Vec<GridLineCell> {
+    cells
+        .into_iter()
+        .map(|cell| GridLineCell {
+            text: cell.text.nfc().collect(),
+            ..cell
+        })
+        .collect()
+}
+
+// Replaces the h<size> part of a guifont setting. The first part holds the font names, so it is
+// never treated as a size.
+fn adjust_guifont_size(guifont: &str, delta: f32) -> Option<String> {
+    let mut parts: Vec<String> = guifont.split(':').map(str::to_owned).collect();
+
+    let size_part = parts
+        .iter_mut()
+        .skip(1)
+        .find(|part| part.starts_with('h') && part[1..].parse::<f32>().is_ok())?;
+    let size: f32 = size_part[1..].parse().ok()?;
+    *size_part = format!("h{}", (size + delta).clamp(MIN_FONT_SIZE, MAX_FONT_SIZE));
+
+    Some(parts.join(":"))
 }
 
 pub fn start_editor() {
@@ -462,3 +884,948 @@ pub fn start_editor() {
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::hash_map::DefaultHasher,
+        hash::{Hash, Hasher},
+    };
+
+    use skia_safe::Color4f;
+
+    use super::*;
+    use crate::{
+        bridge::{EditorMode, WindowAnchor},
+        renderer::{LineFragment, WindowDrawCommand},
+    };
+
+    fn send_redraw_event(editor: &mut Editor, event: RedrawEvent) {
+        editor.handle_editor_command(EditorCommand::NeovimRedrawEvent(event));
+    }
+
+    fn text_cell(text: &str) -> GridLineCell {
+        GridLineCell {
+            text: text.to_owned(),
+            highlight_id: None,
+            repeat: None,
+        }
+    }
+
+    fn line_fragments(draw_commands: Vec<DrawCommand>, grid: u64) -> Vec<LineFragment> {
+        draw_commands
+            .into_iter()
+            .filter_map(|draw_command| match draw_command {
+                DrawCommand::Window {
+                    grid_id,
+                    command: WindowDrawCommand::DrawLine(line_fragments),
+                } if grid_id == grid => Some(line_fragments),
+                _ => None,
+            })
+            .flatten()
+            .collect()
+    }
+
+    fn take_line_fragments(editor: &Editor, grid: u64) -> Vec<LineFragment> {
+        line_fragments(editor.draw_command_batcher.take_batch(), grid)
+    }
+
+    // Returns the rows that got drawn, each once and in order.
+    fn drawn_rows(line_fragments: &[LineFragment]) -> Vec<u64> {
+        let mut rows: Vec<u64> = line_fragments
+            .iter()
+            .map(|line_fragment| line_fragment.window_top)
+            .collect();
+        rows.sort_unstable();
+        rows.dedup();
+        rows
+    }
+
+    fn take_cursor(editor: &Editor) -> Option<Cursor> {
+        editor
+            .draw_command_batcher
+            .take_batch()
+            .into_iter()
+            .find_map(|draw_command| match draw_command {
+                DrawCommand::UpdateCursor(cursor) => Some(cursor),
+                _ => None,
+            })
+    }
+
+    // Hashes the contents of every grid in order of their id, so that the same contents always
+    // give the same hash.
+    fn grid_hash(editor: &Editor) -> u64 {
+        let mut grid_ids: Vec<&u64> = editor.windows.keys().collect();
+        grid_ids.sort();
+
+        let mut hasher = DefaultHasher::new();
+        for grid_id in grid_ids {
+            grid_id.hash(&mut hasher);
+            editor.windows[grid_id].hash_contents(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    fn build_editor(grid_size: (u64, u64)) -> Editor {
+        let mut editor = Editor::new();
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::Resize {
+                grid: 1,
+                width: grid_size.0,
+                height: grid_size.1,
+            },
+        );
+        editor.draw_command_batcher.take_batch();
+        editor
+    }
+
+    #[test]
+    fn grid_line_events_produce_line_fragments_without_a_renderer() {
+        let mut editor = build_editor((10, 1));
+
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::GridLine {
+                grid: 1,
+                row: 0,
+                column_start: 0,
+                cells: vec![text_cell("foo")],
+                wrap: false,
+            },
+        );
+
+        let line_fragments = take_line_fragments(&editor, 1);
+        assert!(line_fragments
+            .iter()
+            .any(|line_fragment| line_fragment.text.starts_with("foo")));
+    }
+
+    #[test]
+    fn message_rows_below_the_base_grid_are_not_drawn() {
+        let mut editor = build_editor((5, 4));
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::Resize {
+                grid: 2,
+                width: 5,
+                height: 4,
+            },
+        );
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::MessageSetPosition {
+                grid: 2,
+                row: 2,
+                scrolled: false,
+                separator_character: "".to_owned(),
+            },
+        );
+        editor.draw_command_batcher.take_batch();
+
+        for row in 0..4 {
+            send_redraw_event(
+                &mut editor,
+                RedrawEvent::GridLine {
+                    grid: 2,
+                    row,
+                    column_start: 0,
+                    cells: vec![text_cell("msg")],
+                    wrap: false,
+                },
+            );
+        }
+
+        assert_eq!(drawn_rows(&take_line_fragments(&editor, 2)), vec![0, 1]);
+    }
+
+    #[test]
+    fn to_text_lines_returns_the_grid_contents() {
+        let mut editor = build_editor((5, 2));
+
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::GridLine {
+                grid: 1,
+                row: 1,
+                column_start: 1,
+                cells: vec![text_cell("bar")],
+                wrap: false,
+            },
+        );
+
+        assert_eq!(
+            editor.to_text_lines(1, true),
+            Some(vec!["".to_owned(), " bar".to_owned()])
+        );
+        assert_eq!(editor.to_text_lines(2, true), None);
+    }
+
+    #[test]
+    fn style_at_and_char_at_read_back_the_grid_cell() {
+        let mut editor = build_editor((5, 1));
+        let mut style = Style::new(Colors::new(None, None, None));
+        style.bold = true;
+
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::HighlightAttributesDefine {
+                id: 1,
+                style: style.clone(),
+                cterm_colors: Colors::new(None, None, None),
+            },
+        );
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::GridLine {
+                grid: 1,
+                row: 0,
+                column_start: 0,
+                cells: vec![
+                    GridLineCell {
+                        text: "a".to_owned(),
+                        highlight_id: Some(1),
+                        repeat: None,
+                    },
+                    GridLineCell {
+                        text: "b".to_owned(),
+                        highlight_id: Some(0),
+                        repeat: None,
+                    },
+                ],
+                wrap: false,
+            },
+        );
+
+        assert_eq!(editor.style_at(1, 0, 0), Some(Arc::new(style)));
+        assert_eq!(editor.char_at(1, 0, 0), Some("a"));
+        assert_eq!(editor.style_at(1, 1, 0), None);
+        assert_eq!(editor.char_at(1, 1, 0), Some("b"));
+        assert_eq!(editor.char_at(1, 5, 0), None);
+        assert_eq!(editor.char_at(1, 0, 1), None);
+        assert_eq!(editor.char_at(2, 0, 0), None);
+    }
+
+    #[test]
+    fn tabline_update_stores_the_tabs_and_buffers() {
+        let mut editor = build_editor((5, 1));
+
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::TablineUpdate {
+                current_tab: Value::from(1),
+                tabs: vec![(Value::from(1), "init.lua".to_owned())],
+                current_buffer: Some(Value::from(2)),
+                buffers: vec![
+                    (Value::from(1), "init.lua".to_owned()),
+                    (Value::from(2), "main.rs".to_owned()),
+                ],
+            },
+        );
+
+        assert_eq!(editor.current_tab, Some(Value::from(1)));
+        assert_eq!(editor.tabs.len(), 1);
+        assert_eq!(editor.current_buffer, Some(Value::from(2)));
+        assert_eq!(
+            editor.buffers,
+            vec![
+                (Value::from(1), "init.lua".to_owned()),
+                (Value::from(2), "main.rs".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn message_events_are_stored_until_cleared() {
+        let mut editor = build_editor((5, 1));
+
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::MessageShow {
+                kind: MessageKind::Echo,
+                content: vec![(0, "first".to_owned())],
+                replace_last: false,
+            },
+        );
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::MessageShow {
+                kind: MessageKind::Error,
+                content: vec![(1, "second".to_owned())],
+                replace_last: true,
+            },
+        );
+
+        assert_eq!(editor.messages.len(), 1);
+        assert!(matches!(editor.messages[0].0, MessageKind::Error));
+        assert_eq!(editor.messages[0].1, vec![(1, "second".to_owned())]);
+
+        send_redraw_event(&mut editor, RedrawEvent::MessageClear);
+        assert!(editor.messages.is_empty());
+    }
+
+    #[test]
+    fn redraw_screen_repaints_without_clearing_the_grid() {
+        let mut editor = build_editor((5, 1));
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::GridLine {
+                grid: 1,
+                row: 0,
+                column_start: 0,
+                cells: vec![text_cell("foo")],
+                wrap: false,
+            },
+        );
+        editor.draw_command_batcher.take_batch();
+
+        editor.handle_editor_command(EditorCommand::RedrawScreen);
+
+        let line_fragments = take_line_fragments(&editor, 1);
+        assert!(line_fragments
+            .iter()
+            .any(|line_fragment| line_fragment.text.starts_with("foo")));
+        assert_eq!(editor.to_text_lines(1, true), Some(vec!["foo".to_owned()]));
+    }
+
+    #[test]
+    fn command_line_events_are_stored_until_hidden() {
+        let mut editor = build_editor((5, 1));
+
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::CommandLineShow {
+                content: vec![(0, "echo".to_owned())],
+                position: 4,
+                first_character: ":".to_owned(),
+                prompt: "".to_owned(),
+                indent: 0,
+                level: 1,
+            },
+        );
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::CommandLineSpecialCharacter {
+                character: "^".to_owned(),
+                shift: true,
+                level: 1,
+            },
+        );
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::CommandLinePosition {
+                position: 2,
+                level: 2,
+            },
+        );
+
+        let command_line = editor.command_line.clone().unwrap();
+        assert_eq!(command_line.content, vec![(0, "echo".to_owned())]);
+        assert_eq!(command_line.first_character, ":");
+        assert_eq!(command_line.position, 4);
+        assert_eq!(command_line.special_character, Some(("^".to_owned(), true)));
+
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::CommandLinePosition {
+                position: 2,
+                level: 1,
+            },
+        );
+        let command_line = editor.command_line.clone().unwrap();
+        assert_eq!(command_line.position, 2);
+        assert_eq!(command_line.special_character, None);
+
+        send_redraw_event(&mut editor, RedrawEvent::CommandLineHide);
+        assert_eq!(editor.command_line, None);
+    }
+
+    #[test]
+    fn command_line_block_lines_are_appended_until_hidden() {
+        let mut editor = build_editor((5, 1));
+
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::CommandLineBlockShow {
+                lines: vec![vec![(0, "function! F()".to_owned())]],
+            },
+        );
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::CommandLineBlockAppend {
+                line: vec![(0, "  echo 1".to_owned())],
+            },
+        );
+        assert_eq!(
+            editor.command_line_block,
+            vec![
+                vec![(0, "function! F()".to_owned())],
+                vec![(0, "  echo 1".to_owned())]
+            ]
+        );
+
+        send_redraw_event(&mut editor, RedrawEvent::CommandLineBlockHide);
+        assert!(editor.command_line_block.is_empty());
+    }
+
+    #[test]
+    fn cursor_is_hidden_while_an_ime_composition_is_active() {
+        let mut editor = build_editor((5, 1));
+
+        assert!(editor.set_ime_composing(true));
+        assert!(!take_cursor(&editor).unwrap().enabled);
+        assert!(!editor.set_ime_composing(true));
+        assert!(take_cursor(&editor).is_none());
+
+        assert!(editor.set_ime_composing(false));
+        assert!(take_cursor(&editor).unwrap().enabled);
+        assert!(editor.cursor_enabled());
+    }
+
+    #[test]
+    fn cterm_colors_are_only_used_with_termguicolors_off() {
+        let mut editor = build_editor((5, 1));
+        let red = Color4f::new(1.0, 0.0, 0.0, 1.0);
+        let define_highlight = |editor: &mut Editor| {
+            send_redraw_event(
+                editor,
+                RedrawEvent::HighlightAttributesDefine {
+                    id: 1,
+                    style: Style::new(Colors::new(None, None, None)),
+                    cterm_colors: Colors::new(Some(red), None, None),
+                },
+            );
+            editor.defined_styles[&1].colors.foreground
+        };
+
+        assert_eq!(define_highlight(&mut editor), None);
+
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::OptionSet {
+                gui_option: GuiOption::TermGuiColors(false),
+            },
+        );
+        assert_eq!(define_highlight(&mut editor), Some(red));
+    }
+
+    #[test]
+    fn popup_menu_follows_the_selection_until_hidden() {
+        let mut editor = build_editor((5, 1));
+        let item = |word: &str| PopupMenuItem {
+            word: word.to_owned(),
+            kind: "v".to_owned(),
+            menu: "".to_owned(),
+            info: "".to_owned(),
+        };
+
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::PopupMenuShow {
+                items: vec![item("foo"), item("bar")],
+                selected: None,
+                row: 1,
+                column: 2,
+                grid: Some(1),
+            },
+        );
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::PopupMenuSelect { selected: Some(1) },
+        );
+
+        let popup_menu = editor.popup_menu.clone().unwrap();
+        assert_eq!(popup_menu.items, vec![item("foo"), item("bar")]);
+        assert_eq!(popup_menu.selected, Some(1));
+        assert_eq!(
+            (popup_menu.grid, popup_menu.row, popup_menu.column),
+            (Some(1), 1, 2)
+        );
+
+        send_redraw_event(&mut editor, RedrawEvent::PopupMenuHide);
+        assert_eq!(editor.popup_menu, None);
+    }
+
+    #[test]
+    fn cursor_goto_moves_the_cursor_into_a_floating_window() {
+        let mut editor = build_editor((20, 10));
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::Resize {
+                grid: 2,
+                width: 5,
+                height: 2,
+            },
+        );
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::WindowFloatPosition {
+                grid: 2,
+                anchor: WindowAnchor::NorthWest,
+                anchor_grid: 1,
+                anchor_row: 1.0,
+                anchor_column: 3.0,
+                focusable: true,
+                sort_order: None,
+            },
+        );
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::CursorGoto {
+                grid: 2,
+                row: 1,
+                column: 1,
+            },
+        );
+
+        assert_eq!(editor.cursor_position(), (2, (1, 1)));
+    }
+
+    #[test]
+    fn default_colors_change_redraws_every_row() {
+        let mut editor = build_editor((5, 3));
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::GridLine {
+                grid: 1,
+                row: 1,
+                column_start: 0,
+                cells: vec![text_cell("foo")],
+                wrap: false,
+            },
+        );
+        editor.draw_command_batcher.take_batch();
+
+        editor.set_default_colors(Colors::new(None, None, None));
+
+        let draw_commands = editor.draw_command_batcher.take_batch();
+        assert!(matches!(
+            draw_commands.first(),
+            Some(DrawCommand::DefaultStyleChanged(_))
+        ));
+        assert_eq!(drawn_rows(&line_fragments(draw_commands, 1)), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn cursor_style_is_resolved_once_its_highlight_is_defined() {
+        let mut editor = build_editor((5, 1));
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::ModeInfoSet {
+                cursor_modes: vec![CursorMode {
+                    style_id: Some(5),
+                    ..Default::default()
+                }],
+            },
+        );
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::ModeChange {
+                mode: EditorMode::Normal,
+                mode_index: 0,
+            },
+        );
+        assert_eq!(editor.cursor.style, None);
+
+        let cursor_style = Style::new(Colors::new(None, None, None));
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::HighlightAttributesDefine {
+                id: 5,
+                style: cursor_style.clone(),
+                cterm_colors: Colors::new(None, None, None),
+            },
+        );
+
+        assert_eq!(editor.cursor.style, Some(Arc::new(cursor_style)));
+    }
+
+    #[test]
+    fn current_cursor_mode_follows_mode_changes() {
+        let mut editor = build_editor((5, 1));
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::ModeInfoSet {
+                cursor_modes: vec![
+                    CursorMode {
+                        name: Some("normal".to_owned()),
+                        ..Default::default()
+                    },
+                    CursorMode {
+                        name: Some("cmdline_normal".to_owned()),
+                        short_name: Some("c".to_owned()),
+                        mouse_shape: Some(3),
+                        ..Default::default()
+                    },
+                ],
+            },
+        );
+        assert!(editor.current_cursor_mode().is_none());
+
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::ModeChange {
+                mode: EditorMode::CmdLine,
+                mode_index: 1,
+            },
+        );
+        let cursor_mode = editor.current_cursor_mode().unwrap();
+        assert_eq!(cursor_mode.name.as_deref(), Some("cmdline_normal"));
+        assert_eq!(cursor_mode.short_name.as_deref(), Some("c"));
+        assert_eq!(cursor_mode.mouse_shape, Some(3));
+    }
+
+    #[test]
+    fn adjust_guifont_size_changes_only_the_size() {
+        assert_eq!(
+            adjust_guifont_size("Fira Code,hack:h12:b", 2.0),
+            Some("Fira Code,hack:h14:b".to_owned())
+        );
+        assert_eq!(
+            adjust_guifont_size("Fira Code:h2", -5.0),
+            Some("Fira Code:h1".to_owned())
+        );
+        assert_eq!(adjust_guifont_size("hack:b", 1.0), None);
+    }
+
+    #[test]
+    fn adjust_font_size_only_zooms_a_guifont_with_a_size() {
+        let mut editor = build_editor((5, 1));
+        assert!(!editor.adjust_font_size(1.0));
+
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::OptionSet {
+                gui_option: GuiOption::GuiFont("hack:b".to_owned()),
+            },
+        );
+        editor.draw_command_batcher.take_batch();
+        assert!(!editor.adjust_font_size(1.0));
+        assert!(editor.draw_command_batcher.take_batch().is_empty());
+
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::OptionSet {
+                gui_option: GuiOption::GuiFont("hack:h12".to_owned()),
+            },
+        );
+        editor.draw_command_batcher.take_batch();
+        assert!(editor.adjust_font_size(1.0));
+        assert!(matches!(
+            editor.draw_command_batcher.take_batch().first(),
+            Some(DrawCommand::FontChanged(guifont)) if guifont == "hack:h13"
+        ));
+    }
+
+    #[test]
+    fn cursor_on_the_second_half_of_a_wide_character_covers_all_of_it() {
+        let mut editor = build_editor((4, 1));
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::GridLine {
+                grid: 1,
+                row: 0,
+                column_start: 0,
+                cells: vec![text_cell("好"), text_cell("")],
+                wrap: false,
+            },
+        );
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::CursorGoto {
+                grid: 1,
+                row: 0,
+                column: 1,
+            },
+        );
+        editor.draw_command_batcher.take_batch();
+
+        editor.send_cursor_info();
+
+        let cursor = take_cursor(&editor).unwrap();
+        assert_eq!(cursor.grid_position, (0, 0));
+        assert_eq!(cursor.grid_cell.0, "好");
+        assert!(cursor.double_width);
+        assert_eq!(editor.cursor_position(), (1, (1, 0)));
+    }
+
+    #[test]
+    fn grid_resize_keeps_the_overlapping_contents() {
+        let mut editor = build_editor((3, 2));
+        for row in 0..2 {
+            send_redraw_event(
+                &mut editor,
+                RedrawEvent::GridLine {
+                    grid: 1,
+                    row,
+                    column_start: 0,
+                    cells: vec![text_cell("abc")],
+                    wrap: false,
+                },
+            );
+        }
+
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::Resize {
+                grid: 1,
+                width: 4,
+                height: 1,
+            },
+        );
+        assert_eq!(
+            editor.to_text_lines(1, false),
+            Some(vec!["abc ".to_owned()])
+        );
+
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::Resize {
+                grid: 1,
+                width: 2,
+                height: 2,
+            },
+        );
+        assert_eq!(
+            editor.to_text_lines(1, false),
+            Some(vec!["ab".to_owned(), "  ".to_owned()])
+        );
+    }
+
+    #[test]
+    fn grid_hash_only_changes_with_the_contents() {
+        let mut editor = build_editor((3, 1));
+        let empty_hash = grid_hash(&editor);
+
+        send_redraw_event(&mut editor, RedrawEvent::Flush);
+        assert_eq!(grid_hash(&editor), empty_hash);
+
+        let grid_line = RedrawEvent::GridLine {
+            grid: 1,
+            row: 0,
+            column_start: 0,
+            cells: vec![text_cell("a")],
+            wrap: false,
+        };
+        send_redraw_event(&mut editor, grid_line.clone());
+        let line_hash = grid_hash(&editor);
+        assert_ne!(line_hash, empty_hash);
+
+        send_redraw_event(&mut editor, grid_line);
+        assert_eq!(grid_hash(&editor), line_hash);
+    }
+
+    #[test]
+    fn linespace_is_forwarded_to_the_renderer() {
+        let mut editor = build_editor((5, 1));
+        editor.draw_command_batcher.take_batch();
+
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::OptionSet {
+                gui_option: GuiOption::LineSpace(-2),
+            },
+        );
+
+        assert!(editor
+            .draw_command_batcher
+            .take_batch()
+            .iter()
+            .any(|draw_command| matches!(draw_command, DrawCommand::LineSpaceChanged(-2))));
+    }
+
+    #[test]
+    fn font_change_redraws_the_grid_without_clearing_it() {
+        let mut editor = build_editor((5, 2));
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::GridLine {
+                grid: 1,
+                row: 1,
+                column_start: 0,
+                cells: vec![text_cell("foo")],
+                wrap: false,
+            },
+        );
+        editor.draw_command_batcher.take_batch();
+
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::OptionSet {
+                gui_option: GuiOption::GuiFont("Fira Code:h14".to_owned()),
+            },
+        );
+
+        let draw_commands = editor.draw_command_batcher.take_batch();
+        assert!(matches!(
+            draw_commands.first(),
+            Some(DrawCommand::FontChanged(guifont)) if guifont == "Fira Code:h14"
+        ));
+        assert_eq!(drawn_rows(&line_fragments(draw_commands, 1)), vec![0, 1]);
+        assert_eq!(
+            editor.to_text_lines(1, true),
+            Some(vec!["".to_owned(), "foo".to_owned()])
+        );
+    }
+
+    #[test]
+    fn visual_bell_is_forwarded_to_the_renderer() {
+        let mut editor = build_editor((5, 1));
+        editor.draw_command_batcher.take_batch();
+
+        send_redraw_event(&mut editor, RedrawEvent::VisualBell);
+
+        assert!(editor
+            .draw_command_batcher
+            .take_batch()
+            .iter()
+            .any(|draw_command| matches!(draw_command, DrawCommand::VisualBell)));
+    }
+
+    #[test]
+    fn suspend_pauses_the_editor_until_the_next_redraw() {
+        let mut editor = build_editor((5, 1));
+
+        send_redraw_event(&mut editor, RedrawEvent::Suspend);
+        assert!(!editor.active);
+
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::GridLine {
+                grid: 1,
+                row: 0,
+                column_start: 0,
+                cells: vec![text_cell("a")],
+                wrap: false,
+            },
+        );
+        assert!(editor.active);
+    }
+
+    #[test]
+    fn apply_events_replays_into_a_fresh_editor() {
+        let events = vec![
+            RedrawEvent::Resize {
+                grid: 1,
+                width: 3,
+                height: 1,
+            },
+            RedrawEvent::GridLine {
+                grid: 1,
+                row: 0,
+                column_start: 0,
+                cells: vec![text_cell("abc")],
+                wrap: false,
+            },
+        ];
+
+        let mut editor = Editor::new();
+        editor.apply_events(&events);
+
+        assert_eq!(editor.to_text_lines(1, false), Some(vec!["abc".to_owned()]));
+    }
+
+    #[test]
+    fn normalize_text_composes_cell_text() {
+        let mut editor = build_editor((2, 1));
+        let decomposed_cell = text_cell("e\u{301}");
+        let grid_line = |cells| RedrawEvent::GridLine {
+            grid: 1,
+            row: 0,
+            column_start: 0,
+            cells,
+            wrap: false,
+        };
+
+        send_redraw_event(&mut editor, grid_line(vec![decomposed_cell.clone()]));
+        assert_eq!(editor.char_at(1, 0, 0), Some("e\u{301}"));
+
+        editor.set_normalize_text(true);
+        send_redraw_event(&mut editor, grid_line(vec![decomposed_cell]));
+        assert_eq!(editor.char_at(1, 0, 0), Some("\u{e9}"));
+        assert_eq!(editor.char_at(1, 1, 0), Some(" "));
+    }
+
+    #[test]
+    fn window_viewport_is_sent_to_the_grid_it_belongs_to() {
+        let mut editor = build_editor((5, 2));
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::WindowViewport {
+                grid: 1,
+                top_line: 10.0,
+                bottom_line: 12.0,
+                current_line: 11.0,
+                current_column: 0.0,
+                line_count: Some(100.0),
+                scroll_delta: Some(3),
+            },
+        );
+
+        let viewports: Vec<(u64, f64, f64)> = editor
+            .draw_command_batcher
+            .take_batch()
+            .into_iter()
+            .filter_map(|draw_command| match draw_command {
+                DrawCommand::Window {
+                    grid_id,
+                    command:
+                        WindowDrawCommand::Viewport {
+                            top_line,
+                            bottom_line,
+                        },
+                } => Some((grid_id, top_line, bottom_line)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(viewports, vec![(1, 10.0, 12.0)]);
+    }
+
+    #[test]
+    fn cursor_getters_follow_the_editor_cursor() {
+        let mut editor = build_editor((5, 2));
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::CursorGoto {
+                grid: 1,
+                row: 1,
+                column: 3,
+            },
+        );
+        send_redraw_event(&mut editor, RedrawEvent::BusyStart);
+
+        assert_eq!(editor.cursor_position(), (1, (3, 1)));
+        assert_eq!(editor.cursor_shape(), &CursorShape::Block);
+        assert!(!editor.cursor_enabled());
+    }
+
+    #[test]
+    fn clear_only_resets_the_targeted_grid() {
+        let mut editor = build_editor((3, 1));
+        send_redraw_event(
+            &mut editor,
+            RedrawEvent::Resize {
+                grid: 2,
+                width: 3,
+                height: 1,
+            },
+        );
+        for grid in [1, 2] {
+            send_redraw_event(
+                &mut editor,
+                RedrawEvent::GridLine {
+                    grid,
+                    row: 0,
+                    column_start: 0,
+                    cells: vec![text_cell("abc")],
+                    wrap: false,
+                },
+            );
+        }
+
+        send_redraw_event(&mut editor, RedrawEvent::Clear { grid: 2 });
+
+        assert_eq!(editor.to_text_lines(1, false), Some(vec!["abc".to_owned()]));
+        assert_eq!(editor.to_text_lines(2, false), Some(vec!["   ".to_owned()]));
+    }
+}
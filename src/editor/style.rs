@@ -1,13 +1,66 @@
+use std::hash::{Hash, Hasher};
+
+#[cfg(feature = "event-recording")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use skia_safe::Color4f;
 
 #[derive(new, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "event-recording", derive(Serialize, Deserialize))]
 pub struct Colors {
+    #[cfg_attr(feature = "event-recording", serde(with = "optional_color"))]
     pub foreground: Option<Color4f>,
+    #[cfg_attr(feature = "event-recording", serde(with = "optional_color"))]
     pub background: Option<Color4f>,
+    #[cfg_attr(feature = "event-recording", serde(with = "optional_color"))]
     pub special: Option<Color4f>,
 }
 
-#[derive(PartialEq, Debug, Clone, Copy)]
+impl Colors {
+    /// Fills the slots that aren't set with the ones from the given fallback colors.
+    pub fn with_fallback(&self, fallback: &Colors) -> Colors {
+        Colors {
+            foreground: self.foreground.or(fallback.foreground),
+            background: self.background.or(fallback.background),
+            special: self.special.or(fallback.special),
+        }
+    }
+}
+
+// Color4f doesn't implement serde's traits, so recorded colors are stored as their components.
+#[cfg(feature = "event-recording")]
+mod optional_color {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        color: &Option<Color4f>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        color
+            .map(|color| [color.r, color.g, color.b, color.a])
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Color4f>, D::Error> {
+        let components = Option::<[f32; 4]>::deserialize(deserializer)?;
+        Ok(components.map(|[r, g, b, a]| Color4f::new(r, g, b, a)))
+    }
+}
+
+// Color4f doesn't implement Hash, so the bits of its components are hashed instead.
+impl Hash for Colors {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for color in [self.foreground, self.background, self.special] {
+            color
+                .map(|color| [color.r, color.g, color.b, color.a].map(f32::to_bits))
+                .hash(state);
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy, Hash)]
+#[cfg_attr(feature = "event-recording", derive(Serialize, Deserialize))]
 pub enum UnderlineStyle {
     Underline,
     UnderDouble,
@@ -16,7 +69,8 @@ pub enum UnderlineStyle {
     UnderCurl,
 }
 
-#[derive(new, Debug, Clone, PartialEq)]
+#[derive(new, Debug, Clone, PartialEq, Hash)]
+#[cfg_attr(feature = "event-recording", derive(Serialize, Deserialize))]
 pub struct Style {
     pub colors: Colors,
     #[new(default)]
@@ -31,11 +85,32 @@ pub struct Style {
     pub blend: u8,
     #[new(default)]
     pub underline: Option<UnderlineStyle>,
+    // Standout is drawn the same way as reverse.
+    #[new(default)]
+    pub standout: bool,
+    // Not used yet, but stored for when highlights get combined.
+    #[new(default)]
+    pub nocombine: bool,
+    // Not rendered differently yet, but keeps runs from being drawn together across altfont
+    // boundaries.
+    #[new(default)]
+    pub altfont: bool,
+    // Marks misspelled text, so that its undercurl can be told apart from one set by syntax
+    // highlighting.
+    #[new(default)]
+    pub spell: bool,
+    // Link target carried by the highlight, so that clicks on the cell can be resolved to it.
+    #[new(default)]
+    pub url: Option<String>,
 }
 
 impl Style {
+    fn is_reversed(&self) -> bool {
+        self.reverse || self.standout
+    }
+
     pub fn foreground(&self, default_colors: &Colors) -> Color4f {
-        if self.reverse {
+        if self.is_reversed() {
             self.colors
                 .background
                 .unwrap_or_else(|| default_colors.background.unwrap())
@@ -47,7 +122,7 @@ impl Style {
     }
 
     pub fn background(&self, default_colors: &Colors) -> Color4f {
-        if self.reverse {
+        if self.is_reversed() {
             self.colors
                 .foreground
                 .unwrap_or_else(|| default_colors.foreground.unwrap())
@@ -61,6 +136,7 @@ impl Style {
     pub fn special(&self, default_colors: &Colors) -> Color4f {
         self.colors
             .special
+            .or(default_colors.special)
             .unwrap_or_else(|| self.foreground(default_colors))
     }
 }
@@ -81,6 +157,19 @@ mod tests {
         special: Some(Color4f::new(0.3, 0.2, 0.1, 0.1)),
     };
 
+    #[test]
+    fn test_with_fallback() {
+        let first_update = Colors::new(COLORS.foreground, None, None);
+        let second_update = Colors::new(None, COLORS.background, None);
+
+        let colors = first_update.with_fallback(&DEFAULT_COLORS);
+        let colors = second_update.with_fallback(&colors);
+
+        assert_eq!(colors.foreground, COLORS.foreground);
+        assert_eq!(colors.background, COLORS.background);
+        assert_eq!(colors.special, DEFAULT_COLORS.special);
+    }
+
     #[test]
     fn test_foreground() {
         let mut style = Style::new(COLORS);
@@ -143,6 +232,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_standout_is_drawn_reversed() {
+        let mut style = Style::new(COLORS);
+        style.standout = true;
+
+        assert_eq!(
+            style.foreground(&DEFAULT_COLORS),
+            COLORS.background.unwrap()
+        );
+        assert_eq!(
+            style.background(&DEFAULT_COLORS),
+            COLORS.foreground.unwrap()
+        );
+    }
+
     #[test]
     fn test_special() {
         let mut style = Style::new(COLORS);
@@ -151,6 +255,15 @@ mod tests {
         style.colors.special = None;
         assert_eq!(
             style.special(&DEFAULT_COLORS),
+            DEFAULT_COLORS.special.unwrap(),
+        );
+
+        let default_colors_without_special = Colors {
+            special: None,
+            ..DEFAULT_COLORS
+        };
+        assert_eq!(
+            style.special(&default_colors_without_special),
             style.foreground(&DEFAULT_COLORS),
         );
     }
@@ -1,4 +1,10 @@
-use std::{collections::HashMap, sync::Arc};
+#[cfg(test)]
+use std::hash::{Hash, Hasher};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use log::warn;
 use unicode_segmentation::UnicodeSegmentation;
@@ -21,6 +27,14 @@ pub struct Window {
 
     pub anchor_info: Option<AnchorInfo>,
     grid_position: (f64, f64),
+    hidden: bool,
+    // Whether each row continues on the next one because of 'wrap'
+    wrapped_rows: Vec<bool>,
+    // Rows outside of this range are kept up to date in the grid but not drawn
+    visible_rows: Option<(u64, u64)>,
+    // Neovim and the gui can briefly disagree on the grid size while resizing, so out of bounds
+    // draws are only logged once in a while.
+    last_out_of_bounds_warning: Option<Instant>,
 
     draw_command_batcher: Arc<DrawCommandBatcher>,
 }
@@ -34,12 +48,17 @@ impl Window {
         grid_size: (u64, u64),
         draw_command_batcher: Arc<DrawCommandBatcher>,
     ) -> Window {
+        let grid = CharacterGrid::new(grid_size);
         let window = Window {
             grid_id,
-            grid: CharacterGrid::new(grid_size),
+            wrapped_rows: vec![false; grid.height as usize],
+            grid,
             window_type,
             anchor_info,
             grid_position,
+            hidden: false,
+            visible_rows: None,
+            last_out_of_bounds_warning: None,
             draw_command_batcher,
         };
         window.send_updated_position();
@@ -67,18 +86,36 @@ impl Window {
         &self,
         window_left: u64,
         window_top: u64,
-    ) -> (String, Option<Arc<Style>>, bool) {
-        let grid_cell = match self.grid.get_cell(window_left, window_top) {
-            Some((character, style)) => (character.clone(), style.clone()),
-            _ => (' '.to_string(), None),
-        };
+    ) -> Option<(String, Option<Arc<Style>>, bool)> {
+        let (character, style) = self.grid.get_cell(window_left, window_top)?;
 
         let double_width = match self.grid.get_cell(window_left + 1, window_top) {
             Some((character, _)) => character.is_empty(),
             _ => false,
         };
 
-        (grid_cell.0, grid_cell.1, double_width)
+        Some((character.clone(), style.clone(), double_width))
+    }
+
+    /// Feeds the size and every cell of the grid into the hasher, row by row.
+    #[cfg(test)]
+    pub fn hash_contents<H: Hasher>(&self, state: &mut H) {
+        (self.grid.width, self.grid.height).hash(state);
+        for row in 0..self.grid.height {
+            for (character, style) in self.grid.row(row).unwrap() {
+                character.hash(state);
+                style.hash(state);
+            }
+        }
+    }
+
+    /// Returns the column of the character covering the given cell. That is the cell itself,
+    /// unless it is the empty second half of a double width character.
+    pub fn character_start(&self, window_left: u64, window_top: u64) -> u64 {
+        match self.grid.get_cell(window_left, window_top) {
+            Some((character, _)) if character.is_empty() && window_left > 0 => window_left - 1,
+            _ => window_left,
+        }
     }
 
     pub fn get_width(&self) -> u64 {
@@ -93,6 +130,40 @@ impl Window {
         self.grid_position
     }
 
+    #[cfg(test)]
+    pub fn style_at(&self, x: u64, y: u64) -> Option<Arc<Style>> {
+        self.grid
+            .get_cell(x, y)
+            .and_then(|(_, style)| style.clone())
+    }
+
+    #[cfg(test)]
+    pub fn char_at(&self, x: u64, y: u64) -> Option<&str> {
+        self.grid
+            .get_cell(x, y)
+            .map(|(character, _)| character.as_str())
+    }
+
+    /// Returns the contents of the grid as one string per row. The empty placeholder cells that
+    /// follow double width characters don't add anything, so each row reads as it is displayed.
+    #[cfg(test)]
+    pub fn to_text_lines(&self, trim_trailing_spaces: bool) -> Vec<String> {
+        (0..self.grid.height)
+            .filter_map(|row_index| self.grid.row(row_index))
+            .map(|row| {
+                let line: String = row
+                    .iter()
+                    .map(|(character, _)| character.as_str())
+                    .collect();
+                if trim_trailing_spaces {
+                    line.trim_end_matches(' ').to_owned()
+                } else {
+                    line
+                }
+            })
+            .collect()
+    }
+
     pub fn position(
         &mut self,
         anchor_info: Option<AnchorInfo>,
@@ -100,6 +171,7 @@ impl Window {
         grid_position: (f64, f64),
     ) {
         self.grid.resize(grid_size);
+        self.wrapped_rows.resize(self.grid.height as usize, false);
         self.anchor_info = anchor_info;
         self.grid_position = grid_position;
         self.send_updated_position();
@@ -108,6 +180,7 @@ impl Window {
 
     pub fn resize(&mut self, new_size: (u64, u64)) {
         self.grid.resize(new_size);
+        self.wrapped_rows.resize(self.grid.height as usize, false);
         self.send_updated_position();
         self.redraw();
     }
@@ -119,7 +192,7 @@ impl Window {
         cell: GridLineCell,
         defined_styles: &HashMap<u64, Arc<Style>>,
         previous_style: &mut Option<Arc<Style>>,
-    ) {
+    ) -> bool {
         // Get the defined style from the style list.
         let style = match cell.highlight_id {
             Some(0) => None,
@@ -127,28 +200,87 @@ impl Window {
             None => previous_style.clone(),
         };
 
-        // Compute text.
-        let mut text = cell.text;
-        if let Some(times) = cell.repeat {
-            text = text.repeat(times as usize);
-        }
+        let text = cell.text;
+
+        // Repeats past the end of the row would never be visible, so they are cut off at the grid
+        // width instead of looping over every one of them.
+        let requested_repeat = cell.repeat.unwrap_or(1);
+        let repeat = requested_repeat.min(self.grid.width.saturating_sub(*column_pos));
+        let mut fits = repeat == requested_repeat;
 
         // Insert the contents of the cell into the grid.
         if text.is_empty() {
-            if let Some(cell) = self.grid.get_cell_mut(*column_pos, row_index) {
-                *cell = (text, style.clone());
-            }
-            *column_pos += 1;
-        } else {
-            for character in text.graphemes(true) {
+            for _ in 0..repeat {
+                // The first half of this character was just written to the cell before, so only
+                // the cell after can be left over from a double width character this overwrites.
+                self.clear_orphaned_second_half(*column_pos, row_index);
                 if let Some(cell) = self.grid.get_cell_mut(*column_pos, row_index) {
-                    *cell = (character.to_string(), style.clone());
+                    *cell = (String::new(), style.clone());
+                } else {
+                    fits = false;
                 }
                 *column_pos += 1;
             }
+        } else {
+            // Segment the text once and write it as many times as it is repeated, instead of
+            // building the repeated string and segmenting all of it.
+            let graphemes: Vec<&str> = text.graphemes(true).collect();
+            for _ in 0..repeat {
+                for character in graphemes.iter() {
+                    self.clear_double_width_remains(*column_pos, row_index);
+                    if let Some(cell) = self.grid.get_cell_mut(*column_pos, row_index) {
+                        *cell = (character.to_string(), style.clone());
+                    } else {
+                        fits = false;
+                    }
+                    *column_pos += 1;
+                }
+            }
         }
 
         *previous_style = style;
+        fits
+    }
+
+    // Writing over one half of a double width character would leave the other half behind, so it
+    // is blanked. When the new character is double width as well, Neovim sends its empty second
+    // half right after, which replaces the blank again.
+    fn clear_double_width_remains(&mut self, x: u64, y: u64) {
+        let is_second_half =
+            matches!(self.grid.get_cell(x, y), Some((character, _)) if character.is_empty());
+        if is_second_half && x > 0 {
+            if let Some((character, _)) = self.grid.get_cell_mut(x - 1, y) {
+                *character = " ".to_owned();
+            }
+        }
+
+        self.clear_orphaned_second_half(x, y);
+    }
+
+    // Blanks the second half of a double width character whose first half is at x and about to be
+    // overwritten.
+    fn clear_orphaned_second_half(&mut self, x: u64, y: u64) {
+        if let Some((character, _)) = self.grid.get_cell_mut(x + 1, y) {
+            if character.is_empty() {
+                *character = " ".to_owned();
+            }
+        }
+    }
+
+    fn warn_out_of_bounds(&mut self, row: u64, column: u64) {
+        let should_warn = self
+            .last_out_of_bounds_warning
+            .map_or(true, |last_warning| {
+                last_warning.elapsed() >= Duration::from_secs(1)
+            });
+
+        if should_warn {
+            warn!(
+                "Draw command out of bounds at row {} column {} of grid {} with size {}x{}",
+                row, column, self.grid_id, self.grid.width, self.grid.height
+            );
+            self.last_out_of_bounds_warning = Some(Instant::now());
+        }
     }
 
     // Build a line fragment for the given row starting from current_start up until the next style
@@ -160,6 +292,7 @@ impl Window {
 
         let mut text = String::new();
         let mut width = 0;
+        let mut blank_cells = Vec::new();
         for possible_end_index in start..self.grid.width {
             let (character, possible_end_style) = &row[possible_end_index as usize];
 
@@ -168,6 +301,10 @@ impl Window {
                 break;
             }
 
+            if self.grid.is_blank(possible_end_index, row_index) {
+                blank_cells.push(width);
+            }
+
             width += 1;
             // The previous character is double width, so send this as its own draw command.
             if character.is_empty() {
@@ -184,6 +321,7 @@ impl Window {
             window_top: row_index,
             width,
             style: style.clone(),
+            blank_cells,
         };
 
         (start + width, line_fragment)
@@ -193,6 +331,10 @@ impl Window {
     // until current_start is greater than the grid width and sending the resulting
     // fragments as a batch.
     fn redraw_line(&self, row: u64) {
+        if !self.is_row_visible(row) {
+            return;
+        }
+
         let mut current_start = 0;
         let mut line_fragments = Vec::new();
         while current_start < self.grid.width {
@@ -213,8 +355,9 @@ impl Window {
         let mut previous_style = None;
         if row < self.grid.height {
             let mut column_pos = column_start;
+            let mut fits = true;
             for cell in cells {
-                self.modify_grid(
+                fits &= self.modify_grid(
                     row,
                     &mut column_pos,
                     cell,
@@ -222,6 +365,9 @@ impl Window {
                     &mut previous_style,
                 );
             }
+            if !fits {
+                self.warn_out_of_bounds(row, column_pos);
+            }
 
             // Due to the limitations of the current rendering strategy, some underlines get
             // clipped by the line below. To mitigate that, we redraw the adjacent lines whenever
@@ -235,10 +381,37 @@ impl Window {
                 self.redraw_line(row - 1);
             }
         } else {
-            warn!("Draw command out of bounds");
+            self.warn_out_of_bounds(row, column_start);
+        }
+    }
+
+    fn is_row_visible(&self, row: u64) -> bool {
+        self.visible_rows
+            .map_or(true, |(top, bottom)| row >= top && row < bottom)
+    }
+
+    /// Limits drawing to the rows from top up to but excluding bottom, or the whole grid when
+    /// None. Rows that become visible are drawn right away.
+    pub fn set_visible_rows(&mut self, visible_rows: Option<(u64, u64)>) {
+        if self.visible_rows != visible_rows {
+            self.visible_rows = visible_rows;
+            self.redraw();
+        }
+    }
+
+    pub fn set_row_wrap(&mut self, row: u64, wrap: bool) {
+        if let Some(wrapped) = self.wrapped_rows.get_mut(row as usize) {
+            *wrapped = wrap;
         }
     }
 
+    pub fn is_row_wrapped(&self, row: u64) -> bool {
+        self.wrapped_rows
+            .get(row as usize)
+            .copied()
+            .unwrap_or(false)
+    }
+
     pub fn scroll_region(
         &mut self,
         top: u64,
@@ -248,6 +421,16 @@ impl Window {
         rows: i64,
         cols: i64,
     ) {
+        let bottom = bottom.min(self.grid.height);
+        let right = right.min(self.grid.width);
+        if top >= bottom || left >= right {
+            warn!(
+                "Ignoring scroll of empty region {}..{} x {}..{} in grid {}",
+                top, bottom, left, right, self.grid_id
+            );
+            return;
+        }
+
         let mut top_to_bottom;
         let mut bottom_to_top;
         let y_iter: &mut dyn Iterator<Item = i64> = if rows > 0 {
@@ -267,6 +450,10 @@ impl Window {
             cols,
         });
 
+        // Wrapping is a property of the whole row, so it only moves along with rows that are
+        // scrolled across the full width of the grid.
+        let full_width = left == 0 && right == self.grid.width;
+
         // Scrolls must not only translate the rendered texture, but also must move the grid data
         // accordingly so that future renders work correctly.
         for y in y_iter {
@@ -274,6 +461,11 @@ impl Window {
             let mut cols_left;
             let mut cols_right;
             if dest_y >= 0 && dest_y < self.grid.height as i64 {
+                if full_width {
+                    let wrap = self.is_row_wrapped(y as u64);
+                    self.set_row_wrap(dest_y as u64, wrap);
+                }
+
                 let x_iter: &mut dyn Iterator<Item = i64> = if cols > 0 {
                     cols_left = (left as i64 + cols)..right as i64;
                     &mut cols_left
@@ -284,22 +476,59 @@ impl Window {
 
                 for x in x_iter {
                     let dest_x = x - cols;
-                    let cell_data = self.grid.get_cell(x as u64, y as u64).cloned();
-
-                    if let Some(cell_data) = cell_data {
-                        if let Some(dest_cell) =
-                            self.grid.get_cell_mut(dest_x as u64, dest_y as u64)
-                        {
-                            *dest_cell = cell_data;
-                        }
-                    }
+                    self.grid
+                        .copy_cell((x as u64, y as u64), (dest_x as u64, dest_y as u64));
                 }
             }
         }
+
+        // Neovim fills the region exposed by the scroll with follow up grid_line events, but until
+        // then those cells still hold the content that was scrolled away. Reset them so that
+        // redrawing an adjacent line never paints the stale copy.
+        let (top, bottom, left, right) = (top as i64, bottom as i64, left as i64, right as i64);
+        let (vacated_top, vacated_bottom) = if rows > 0 {
+            ((bottom - rows).max(top), bottom)
+        } else {
+            (top, (top - rows).min(bottom))
+        };
+        let (vacated_left, vacated_right) = if cols > 0 {
+            ((right - cols).max(left), right)
+        } else {
+            (left, (left - cols).min(right))
+        };
+        self.grid.clear_region(
+            vacated_top as u64,
+            vacated_bottom as u64,
+            left as u64,
+            right as u64,
+        );
+        self.grid.clear_region(
+            top as u64,
+            bottom as u64,
+            vacated_left as u64,
+            vacated_right as u64,
+        );
+        if full_width {
+            for y in vacated_top..vacated_bottom {
+                self.set_row_wrap(y as u64, false);
+            }
+        }
+
+        // The scrolled texture still shows the old content where the rows were vacated, so they
+        // are drawn again right away instead of waiting for Neovim to fill them.
+        let redrawn_rows = if cols != 0 {
+            top..bottom
+        } else {
+            vacated_top..vacated_bottom
+        };
+        for row in redrawn_rows {
+            self.redraw_line(row as u64);
+        }
     }
 
     pub fn clear(&mut self) {
         self.grid.clear();
+        self.wrapped_rows.fill(false);
         self.send_command(WindowDrawCommand::Clear);
     }
 
@@ -312,12 +541,18 @@ impl Window {
         }
     }
 
-    pub fn hide(&self) {
+    pub fn hide(&mut self) {
+        self.hidden = true;
         self.send_command(WindowDrawCommand::Hide);
     }
 
-    pub fn show(&self) {
-        self.send_command(WindowDrawCommand::Show);
+    pub fn show(&mut self) {
+        // Windows are repositioned and shown on every win_pos, so only tell the renderer when the
+        // window was actually hidden.
+        if self.hidden {
+            self.hidden = false;
+            self.send_command(WindowDrawCommand::Show);
+        }
     }
 
     pub fn close(&self) {
@@ -336,8 +571,63 @@ impl Window {
 mod tests {
     use std::collections::HashMap;
 
+    use skia_safe::Color4f;
+
     use super::*;
-    use crate::event_aggregator::EVENT_AGGREGATOR;
+    use crate::{editor::Colors, event_aggregator::EVENT_AGGREGATOR};
+
+    fn build_test_window(grid_size: (u64, u64)) -> Window {
+        Window::new(
+            1,
+            WindowType::Editor,
+            None,
+            (0.0, 0.0),
+            grid_size,
+            Arc::new(DrawCommandBatcher::new()),
+        )
+    }
+
+    fn text_cell(text: &str) -> GridLineCell {
+        GridLineCell {
+            text: text.to_owned(),
+            highlight_id: None,
+            repeat: None,
+        }
+    }
+
+    fn row_text(window: &Window, row: u64) -> String {
+        window
+            .grid
+            .row(row)
+            .unwrap()
+            .iter()
+            .map(|(character, _)| character.as_str())
+            .collect()
+    }
+
+    fn take_line_fragments(window: &Window) -> Vec<LineFragment> {
+        window
+            .draw_command_batcher
+            .take_batch()
+            .into_iter()
+            .filter_map(|draw_command| match draw_command {
+                DrawCommand::Window {
+                    command: WindowDrawCommand::DrawLine(line_fragments),
+                    ..
+                } => Some(line_fragments),
+                _ => None,
+            })
+            .flatten()
+            .collect()
+    }
+
+    fn build_numbered_window(grid_size: (u64, u64)) -> Window {
+        let mut window = build_test_window(grid_size);
+        for row in 0..grid_size.1 {
+            window.draw_grid_line(row, 0, vec![text_cell(&row.to_string())], &HashMap::new());
+        }
+        window
+    }
 
     #[test]
     fn window_separator_modifies_grid_and_sends_draw_command() {
@@ -379,4 +669,494 @@ mod tests {
             .expect("Could not receive commands");
         assert!(!sent_commands.is_empty());
     }
+
+    #[test]
+    fn double_width_character_is_drawn_across_both_cells() {
+        let mut window = build_test_window((10, 1));
+
+        window.draw_grid_line(
+            0,
+            0,
+            vec![text_cell("中"), text_cell(""), text_cell("a")],
+            &HashMap::new(),
+        );
+
+        let (next_start, line_fragment) = window.build_line_fragment(0, 0);
+        assert_eq!(next_start, 2);
+        assert_eq!(line_fragment.text, "中");
+        assert_eq!(line_fragment.width, 2);
+
+        let (_, line_fragment) = window.build_line_fragment(0, next_start);
+        assert_eq!(line_fragment.window_left, 2);
+        assert!(line_fragment.text.starts_with('a'));
+    }
+
+    #[test]
+    fn hiding_a_window_keeps_its_contents() {
+        let mut window = build_test_window((10, 1));
+        window.draw_grid_line(0, 0, vec![text_cell("a")], &HashMap::new());
+
+        window.hide();
+        assert!(window.hidden);
+        assert_eq!(window.grid.get_cell(0, 0), Some(&("a".to_owned(), None)));
+
+        window.show();
+        assert!(!window.hidden);
+    }
+
+    #[test]
+    fn grid_line_past_the_last_row_is_ignored() {
+        let mut window = build_test_window((2, 2));
+
+        window.draw_grid_line(2, 0, vec![text_cell("ab")], &HashMap::new());
+
+        assert_eq!(window.to_text_lines(false), vec!["  ", "  "]);
+    }
+
+    #[test]
+    fn previous_style_does_not_carry_over_between_grid_lines() {
+        let mut window = build_test_window((2, 2));
+        let style = Arc::new(Style::new(Colors::new(None, None, None)));
+        let mut defined_styles = HashMap::new();
+        defined_styles.insert(1, style.clone());
+
+        window.draw_grid_line(
+            0,
+            0,
+            vec![
+                GridLineCell {
+                    text: "a".to_owned(),
+                    highlight_id: Some(1),
+                    repeat: None,
+                },
+                text_cell("b"),
+            ],
+            &defined_styles,
+        );
+        window.draw_grid_line(1, 0, vec![text_cell("c")], &defined_styles);
+
+        assert_eq!(window.style_at(1, 0), Some(style));
+        assert_eq!(window.style_at(0, 1), None);
+    }
+
+    #[test]
+    fn zwj_emoji_sequences_are_kept_in_one_cell() {
+        let mut window = build_test_window((4, 1));
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+
+        window.draw_grid_line(
+            0,
+            1,
+            vec![text_cell(family), text_cell(""), text_cell("a")],
+            &HashMap::new(),
+        );
+
+        assert_eq!(window.char_at(1, 0), Some(family));
+        assert_eq!(window.char_at(2, 0), Some(""));
+        assert_eq!(window.char_at(3, 0), Some("a"));
+    }
+
+    #[test]
+    fn overwriting_half_of_a_double_width_character_clears_the_other_half() {
+        let mut window = build_test_window((4, 1));
+        let double_width_cells = || vec![text_cell("好"), text_cell("")];
+
+        window.draw_grid_line(0, 0, double_width_cells(), &HashMap::new());
+        window.draw_grid_line(0, 0, vec![text_cell("a")], &HashMap::new());
+        assert_eq!(row_text(&window, 0), "a   ");
+
+        window.draw_grid_line(0, 0, double_width_cells(), &HashMap::new());
+        window.draw_grid_line(0, 1, vec![text_cell("b")], &HashMap::new());
+        assert_eq!(row_text(&window, 0), " b  ");
+
+        window.draw_grid_line(0, 0, double_width_cells(), &HashMap::new());
+        window.draw_grid_line(0, 0, double_width_cells(), &HashMap::new());
+        assert_eq!(row_text(&window, 0), "好  ");
+    }
+
+    #[test]
+    fn a_second_half_over_the_first_half_of_another_character_clears_its_second_half() {
+        let mut window = build_test_window((4, 1));
+        let double_width_cells = || vec![text_cell("好"), text_cell("")];
+
+        window.draw_grid_line(0, 1, double_width_cells(), &HashMap::new());
+        window.draw_grid_line(0, 0, double_width_cells(), &HashMap::new());
+
+        assert_eq!(row_text(&window, 0), "好  ");
+        assert_eq!(window.grid.get_cell(2, 0), Some(&(" ".to_owned(), None)));
+    }
+
+    #[test]
+    fn scrolling_an_inverted_region_does_nothing() {
+        let mut window = build_numbered_window((1, 3));
+        window.draw_command_batcher.take_batch();
+
+        window.scroll_region(2, 1, 0, 1, 1, 0);
+        window.scroll_region(0, 3, 1, 0, 1, 0);
+
+        assert_eq!(window.to_text_lines(false), vec!["0", "1", "2"]);
+        assert!(window.draw_command_batcher.take_batch().is_empty());
+    }
+
+    #[test]
+    fn grid_line_wider_than_the_grid_is_cut_off() {
+        let mut window = build_test_window((3, 1));
+        window.draw_grid_line(
+            0,
+            1,
+            vec![text_cell("abc"), text_cell(""), text_cell("d")],
+            &HashMap::new(),
+        );
+        window.draw_grid_line(0, 5, vec![text_cell("e")], &HashMap::new());
+
+        assert_eq!(row_text(&window, 0), " ab");
+        assert!(window.last_out_of_bounds_warning.is_some());
+    }
+
+    #[test]
+    fn zero_repeat_writes_no_cells() {
+        let mut window = build_test_window((3, 1));
+        window.draw_grid_line(
+            0,
+            0,
+            vec![
+                GridLineCell {
+                    text: "a".to_owned(),
+                    highlight_id: None,
+                    repeat: Some(0),
+                },
+                GridLineCell {
+                    text: String::new(),
+                    highlight_id: None,
+                    repeat: Some(0),
+                },
+                text_cell("b"),
+            ],
+            &HashMap::new(),
+        );
+
+        assert_eq!(row_text(&window, 0), "b  ");
+    }
+
+    #[test]
+    fn oversized_repeat_stops_at_the_grid_width() {
+        let mut window = build_test_window((3, 1));
+        window.draw_grid_line(
+            0,
+            1,
+            vec![GridLineCell {
+                text: "a".to_owned(),
+                highlight_id: None,
+                repeat: Some(u64::MAX),
+            }],
+            &HashMap::new(),
+        );
+
+        assert_eq!(row_text(&window, 0), " aa");
+    }
+
+    #[test]
+    fn url_is_kept_with_the_cell_style() {
+        let mut window = build_test_window((3, 1));
+        let mut link_style = Style::new(Colors::new(None, None, None));
+        link_style.url = Some("https://neovim.io".to_owned());
+        let mut defined_styles = HashMap::new();
+        defined_styles.insert(1, Arc::new(link_style));
+
+        window.draw_grid_line(
+            0,
+            0,
+            vec![
+                GridLineCell {
+                    text: "a".to_owned(),
+                    highlight_id: Some(1),
+                    repeat: Some(2),
+                },
+                GridLineCell {
+                    text: "b".to_owned(),
+                    highlight_id: Some(0),
+                    repeat: None,
+                },
+            ],
+            &defined_styles,
+        );
+
+        let url_at = |x| window.style_at(x, 0).and_then(|style| style.url.clone());
+        assert_eq!(url_at(0).as_deref(), Some("https://neovim.io"));
+        assert_eq!(url_at(1).as_deref(), Some("https://neovim.io"));
+        assert_eq!(url_at(2), None);
+    }
+
+    #[test]
+    fn altfont_cells_are_drawn_in_their_own_fragment() {
+        let mut window = build_test_window((4, 1));
+        let mut altfont_style = Style::new(Colors::new(None, None, None));
+        altfont_style.altfont = true;
+        let mut defined_styles = HashMap::new();
+        defined_styles.insert(1, Arc::new(Style::new(Colors::new(None, None, None))));
+        defined_styles.insert(2, Arc::new(altfont_style));
+
+        window.draw_grid_line(
+            0,
+            0,
+            vec![
+                GridLineCell {
+                    text: "ab".to_owned(),
+                    highlight_id: Some(1),
+                    repeat: None,
+                },
+                GridLineCell {
+                    text: "cd".to_owned(),
+                    highlight_id: Some(2),
+                    repeat: None,
+                },
+            ],
+            &defined_styles,
+        );
+
+        let (next_start, line_fragment) = window.build_line_fragment(0, 0);
+        assert_eq!(next_start, 2);
+        assert_eq!(line_fragment.text, "ab");
+    }
+
+    #[test]
+    fn spell_cells_are_drawn_in_their_own_fragment() {
+        let mut window = build_test_window((4, 1));
+        let mut spell_style = Style::new(Colors::new(None, None, None));
+        spell_style.spell = true;
+        let mut defined_styles = HashMap::new();
+        defined_styles.insert(1, Arc::new(Style::new(Colors::new(None, None, None))));
+        defined_styles.insert(2, Arc::new(spell_style));
+
+        window.draw_grid_line(
+            0,
+            0,
+            vec![
+                GridLineCell {
+                    text: "ab".to_owned(),
+                    highlight_id: Some(1),
+                    repeat: None,
+                },
+                GridLineCell {
+                    text: "cd".to_owned(),
+                    highlight_id: Some(2),
+                    repeat: None,
+                },
+            ],
+            &defined_styles,
+        );
+
+        let (next_start, line_fragment) = window.build_line_fragment(0, 2);
+        assert_eq!(next_start, 4);
+        assert_eq!(line_fragment.text, "cd");
+        assert!(line_fragment.style.unwrap().spell);
+    }
+
+    #[test]
+    fn cursor_grid_cell_is_none_outside_of_the_grid() {
+        let mut window = build_test_window((2, 1));
+        window.draw_grid_line(0, 0, vec![text_cell("中"), text_cell("")], &HashMap::new());
+
+        assert_eq!(
+            window.get_cursor_grid_cell(0, 0),
+            Some(("中".to_owned(), None, true))
+        );
+        assert_eq!(window.get_cursor_grid_cell(2, 0), None);
+        assert_eq!(window.get_cursor_grid_cell(0, 1), None);
+    }
+
+    #[test]
+    fn cursor_grid_cell_keeps_combining_characters_in_one_cell() {
+        let mut window = build_test_window((3, 1));
+        window.draw_grid_line(
+            0,
+            0,
+            vec![text_cell("e\u{301}"), text_cell("a")],
+            &HashMap::new(),
+        );
+
+        assert_eq!(
+            window.get_cursor_grid_cell(0, 0),
+            Some(("e\u{301}".to_owned(), None, false))
+        );
+        assert_eq!(
+            window.get_cursor_grid_cell(1, 0),
+            Some(("a".to_owned(), None, false))
+        );
+    }
+
+    #[test]
+    fn scroll_region_up_moves_every_row_including_the_last() {
+        let mut window = build_numbered_window((1, 4));
+
+        window.scroll_region(0, 4, 0, 1, 1, 0);
+
+        assert_eq!(row_text(&window, 0), "1");
+        assert_eq!(row_text(&window, 1), "2");
+        assert_eq!(row_text(&window, 2), "3");
+        assert_eq!(row_text(&window, 3), " ");
+    }
+
+    #[test]
+    fn scroll_region_down_moves_every_row_including_the_first() {
+        let mut window = build_numbered_window((1, 4));
+
+        window.scroll_region(0, 4, 0, 1, -1, 0);
+
+        assert_eq!(row_text(&window, 0), " ");
+        assert_eq!(row_text(&window, 1), "0");
+        assert_eq!(row_text(&window, 2), "1");
+        assert_eq!(row_text(&window, 3), "2");
+    }
+
+    #[test]
+    fn scroll_region_only_touches_the_given_rows() {
+        let mut window = build_numbered_window((1, 5));
+
+        window.scroll_region(1, 4, 0, 1, 1, 0);
+
+        assert_eq!(row_text(&window, 0), "0");
+        assert_eq!(row_text(&window, 1), "2");
+        assert_eq!(row_text(&window, 2), "3");
+        assert_eq!(row_text(&window, 3), " ");
+        assert_eq!(row_text(&window, 4), "4");
+    }
+
+    #[test]
+    fn vacated_rows_are_redrawn_after_a_scroll() {
+        let mut window = build_numbered_window((1, 5));
+        window.draw_command_batcher.take_batch();
+
+        window.scroll_region(0, 5, 0, 1, 2, 0);
+
+        let line_fragments = take_line_fragments(&window);
+        let mut drawn_rows: Vec<u64> = line_fragments
+            .iter()
+            .map(|line_fragment| line_fragment.window_top)
+            .collect();
+        drawn_rows.sort_unstable();
+        assert_eq!(drawn_rows, vec![3, 4]);
+        assert!(line_fragments
+            .iter()
+            .all(|line_fragment| line_fragment.text == " "));
+    }
+
+    #[test]
+    fn only_cells_that_were_never_drawn_to_are_blank() {
+        let mut window = build_test_window((5, 1));
+        window.draw_grid_line(0, 0, vec![text_cell("a b")], &HashMap::new());
+
+        let line_fragments = take_line_fragments(&window);
+        let line_fragment = line_fragments.last().unwrap();
+        assert_eq!(line_fragment.text, "a b  ");
+        assert_eq!(line_fragment.blank_cells, vec![3, 4]);
+
+        // Cells vacated by a scroll are blank again
+        window.scroll_region(0, 1, 0, 5, 0, 2);
+        let line_fragments = take_line_fragments(&window);
+        assert_eq!(line_fragments.last().unwrap().blank_cells, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn row_wrap_moves_with_full_width_scrolls() {
+        let mut window = build_numbered_window((1, 3));
+        window.set_row_wrap(1, true);
+
+        window.scroll_region(0, 3, 0, 1, 1, 0);
+        assert!(window.is_row_wrapped(0));
+        assert!(!window.is_row_wrapped(1));
+
+        window.clear();
+        assert!(!window.is_row_wrapped(0));
+    }
+
+    #[test]
+    fn rows_outside_of_the_visible_rows_are_not_drawn() {
+        let mut window = build_test_window((1, 4));
+        window.set_visible_rows(Some((0, 1)));
+        window.draw_command_batcher.take_batch();
+
+        window.draw_grid_line(3, 0, vec![text_cell("a")], &HashMap::new());
+
+        assert!(take_line_fragments(&window).is_empty());
+        assert_eq!(row_text(&window, 3), "a");
+    }
+
+    #[test]
+    fn to_text_lines_joins_double_width_characters() {
+        let mut window = build_test_window((4, 2));
+        window.draw_grid_line(
+            0,
+            0,
+            vec![text_cell("中"), text_cell(""), text_cell("a")],
+            &HashMap::new(),
+        );
+
+        assert_eq!(window.to_text_lines(false), vec!["中a ", "    "]);
+        assert_eq!(window.to_text_lines(true), vec!["中a", ""]);
+    }
+
+    #[test]
+    fn styled_trailing_spaces_are_drawn_as_one_fragment() {
+        let mut window = build_test_window((6, 1));
+        let mut defined_styles = HashMap::new();
+        defined_styles.insert(
+            1,
+            Arc::new(Style::new(Colors::new(
+                None,
+                Some(Color4f::new(1.0, 0.0, 0.0, 1.0)),
+                None,
+            ))),
+        );
+
+        window.draw_grid_line(
+            0,
+            0,
+            vec![
+                text_cell("ab"),
+                GridLineCell {
+                    text: " ".to_owned(),
+                    highlight_id: Some(1),
+                    repeat: Some(4),
+                },
+            ],
+            &defined_styles,
+        );
+
+        let (next_start, line_fragment) = window.build_line_fragment(0, 2);
+        assert_eq!(next_start, 6);
+        assert_eq!(line_fragment.text, "    ");
+        assert_eq!(line_fragment.width, 4);
+        assert_eq!(line_fragment.style, defined_styles.get(&1).cloned());
+    }
+
+    #[test]
+    fn styled_spaces_are_drawn_as_one_fragment_per_row() {
+        let mut window = build_test_window((80, 24));
+        let mut defined_styles = HashMap::new();
+        defined_styles.insert(1, Arc::new(Style::new(Colors::new(None, None, None))));
+
+        for row in 0..24 {
+            window.draw_grid_line(
+                row,
+                0,
+                vec![GridLineCell {
+                    text: " ".to_owned(),
+                    highlight_id: Some(1),
+                    repeat: Some(80),
+                }],
+                &defined_styles,
+            );
+        }
+        window.draw_command_batcher.take_batch();
+
+        window.redraw();
+
+        let line_fragments = take_line_fragments(&window);
+
+        assert_eq!(line_fragments.len(), 24);
+        assert!(line_fragments
+            .iter()
+            .all(|line_fragment| line_fragment.width == 80));
+    }
 }
@@ -0,0 +1,36 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Only one in this many calls of a sampled trace is logged.
+pub const TRACE_SAMPLE_INTERVAL: u64 = 100;
+
+/// Logs at trace level from paths that run many times per frame, such as queueing frames or
+/// shaping text. Only every TRACE_SAMPLE_INTERVAL-th call of each call site is logged, so that
+/// enabling trace logging doesn't flood the log or skew timings.
+macro_rules! trace_sampled {
+    ($($arg:tt)+) => {{
+        static CALLS: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+        if log::log_enabled!(log::Level::Trace) && $crate::log_utils::sample(&CALLS) {
+            log::trace!($($arg)+);
+        }
+    }};
+}
+
+pub fn sample(calls: &AtomicU64) -> bool {
+    calls.fetch_add(1, Ordering::Relaxed) % TRACE_SAMPLE_INTERVAL == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_lets_one_call_per_interval_through() {
+        let calls = AtomicU64::new(0);
+
+        let sampled = (0..TRACE_SAMPLE_INTERVAL * 3)
+            .filter(|_| sample(&calls))
+            .count();
+
+        assert_eq!(sampled, 3);
+    }
+}
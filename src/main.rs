@@ -8,6 +8,9 @@ extern crate neovide_derive;
 #[macro_use]
 extern crate clap;
 
+#[macro_use]
+mod log_utils;
+
 mod bridge;
 mod channel_utils;
 mod clipboard;
@@ -3,10 +3,12 @@ use std::{
         atomic::{AtomicBool, Ordering},
         Mutex,
     },
-    time::Instant,
+    time::{Duration, Instant},
 };
 
-use log::trace;
+// Longest interval that can be set between frames, so that animations like the cursor trail still
+// get enough frames to look smooth.
+const MAX_MIN_FRAME_INTERVAL: Duration = Duration::from_millis(33);
 
 lazy_static! {
     pub static ref REDRAW_SCHEDULER: RedrawScheduler = RedrawScheduler::new();
@@ -15,6 +17,8 @@ lazy_static! {
 pub struct RedrawScheduler {
     scheduled_frame: Mutex<Option<Instant>>,
     frame_queued: AtomicBool,
+    min_frame_interval: Mutex<Option<Duration>>,
+    last_frame: Mutex<Option<Instant>>,
 }
 
 impl RedrawScheduler {
@@ -22,11 +26,13 @@ impl RedrawScheduler {
         RedrawScheduler {
             scheduled_frame: Mutex::new(None),
             frame_queued: AtomicBool::new(true),
+            min_frame_interval: Mutex::new(None),
+            last_frame: Mutex::new(None),
         }
     }
 
     pub fn schedule(&self, new_scheduled: Instant) {
-        trace!("Redraw scheduled for {:?}", new_scheduled);
+        trace_sampled!("Redraw scheduled for {:?}", new_scheduled);
         let mut scheduled_frame = self.scheduled_frame.lock().unwrap();
 
         if let Some(previous_scheduled) = *scheduled_frame {
@@ -39,12 +45,36 @@ impl RedrawScheduler {
     }
 
     pub fn queue_next_frame(&self) {
-        trace!("Next frame queued");
+        trace_sampled!("Next frame queued");
         self.frame_queued.store(true, Ordering::Relaxed);
     }
 
+    /// Coalesces queued and scheduled frames so that at most one is drawn per interval. None draws
+    /// them as soon as they are polled.
+    pub fn set_min_frame_interval(&self, interval: Option<Duration>) {
+        *self.min_frame_interval.lock().unwrap() =
+            interval.map(|interval| interval.min(MAX_MIN_FRAME_INTERVAL));
+    }
+
+    fn within_min_frame_interval(&self) -> bool {
+        let min_frame_interval = *self.min_frame_interval.lock().unwrap();
+        let last_frame = *self.last_frame.lock().unwrap();
+
+        match (min_frame_interval, last_frame) {
+            (Some(min_frame_interval), Some(last_frame)) => {
+                last_frame.elapsed() < min_frame_interval
+            }
+            _ => false,
+        }
+    }
+
     pub fn should_draw(&self) -> bool {
-        if self.frame_queued.load(Ordering::Relaxed) {
+        // Queued and scheduled frames are both left pending until the interval has passed
+        if self.within_min_frame_interval() {
+            return false;
+        }
+
+        let should_draw = if self.frame_queued.load(Ordering::Relaxed) {
             self.frame_queued.store(false, Ordering::Relaxed);
             true
         } else {
@@ -60,6 +90,65 @@ impl RedrawScheduler {
             } else {
                 false
             }
+        };
+
+        if should_draw {
+            *self.last_frame.lock().unwrap() = Some(Instant::now());
         }
+        should_draw
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queued_frames_are_coalesced_within_the_interval() {
+        let redraw_scheduler = RedrawScheduler::new();
+        redraw_scheduler.set_min_frame_interval(Some(Duration::from_millis(30)));
+
+        assert!(redraw_scheduler.should_draw());
+        redraw_scheduler.queue_next_frame();
+        redraw_scheduler.queue_next_frame();
+        assert!(!redraw_scheduler.should_draw());
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(redraw_scheduler.should_draw());
+        assert!(!redraw_scheduler.should_draw());
+    }
+
+    #[test]
+    fn scheduled_frames_wait_for_the_interval() {
+        let redraw_scheduler = RedrawScheduler::new();
+        redraw_scheduler.set_min_frame_interval(Some(Duration::from_millis(30)));
+
+        assert!(redraw_scheduler.should_draw());
+        redraw_scheduler.schedule(Instant::now());
+        assert!(!redraw_scheduler.should_draw());
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(redraw_scheduler.should_draw());
+        assert!(!redraw_scheduler.should_draw());
+    }
+
+    #[test]
+    fn the_interval_is_capped_for_the_cursor_animation() {
+        let redraw_scheduler = RedrawScheduler::new();
+        redraw_scheduler.set_min_frame_interval(Some(Duration::from_secs(1)));
+
+        assert_eq!(
+            *redraw_scheduler.min_frame_interval.lock().unwrap(),
+            Some(MAX_MIN_FRAME_INTERVAL)
+        );
+    }
+
+    #[test]
+    fn queued_frames_are_drawn_right_away_without_an_interval() {
+        let redraw_scheduler = RedrawScheduler::new();
+
+        assert!(redraw_scheduler.should_draw());
+        redraw_scheduler.queue_next_frame();
+        assert!(redraw_scheduler.should_draw());
     }
 }
@@ -79,3 +79,39 @@ impl BlinkStatus {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_blink_durations_keep_the_cursor_solid() {
+        let mut cursor = Cursor::new();
+        cursor.blinkwait = Some(0);
+        cursor.blinkon = Some(500);
+        cursor.blinkoff = Some(500);
+
+        let mut blink_status = BlinkStatus::new();
+        assert!(blink_status.update_status(&cursor));
+
+        cursor.blinkwait = Some(100);
+        cursor.blinkoff = Some(0);
+        assert!(blink_status.update_status(&cursor));
+    }
+
+    #[test]
+    fn moving_the_cursor_resets_the_blink_phase() {
+        let mut cursor = Cursor::new();
+        cursor.blinkwait = Some(1000);
+        cursor.blinkon = Some(500);
+        cursor.blinkoff = Some(500);
+
+        let mut blink_status = BlinkStatus::new();
+        blink_status.update_status(&cursor);
+        blink_status.state = BlinkState::Off;
+
+        cursor.grid_position = (1, 0);
+        assert!(blink_status.update_status(&cursor));
+        assert!(matches!(blink_status.state, BlinkState::Waiting));
+    }
+}
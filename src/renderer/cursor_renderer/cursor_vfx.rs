@@ -3,7 +3,6 @@ use nvim_rs::Value;
 use skia_safe::{paint::Style, BlendMode, Canvas, Color, Paint, Point, Rect};
 
 use crate::{
-    editor::Cursor,
     renderer::cursor_renderer::CursorSettings,
     renderer::{animation_utils::*, grid_renderer::GridRenderer},
     settings::*,
@@ -23,7 +22,7 @@ pub trait CursorVfx {
         settings: &CursorSettings,
         canvas: &mut Canvas,
         grid_renderer: &mut GridRenderer,
-        cursor: &Cursor,
+        base_color: Color,
     );
 }
 
@@ -130,7 +129,7 @@ impl CursorVfx for PointHighlight {
         settings: &CursorSettings,
         canvas: &mut Canvas,
         grid_renderer: &mut GridRenderer,
-        cursor: &Cursor,
+        base_color: Color,
     ) {
         if (self.t - 1.0).abs() < std::f32::EPSILON {
             return;
@@ -139,8 +138,6 @@ impl CursorVfx for PointHighlight {
         let mut paint = Paint::new(skia_safe::colors::WHITE, None);
         paint.set_blend_mode(BlendMode::SrcOver);
 
-        let colors = &grid_renderer.default_style.colors;
-        let base_color: Color = cursor.background(colors).to_color();
         let alpha = ease(ease_in_quad, settings.vfx_opacity, 0.0, self.t) as u8;
         let color = Color::from_argb(alpha, base_color.r(), base_color.g(), base_color.b());
 
@@ -320,7 +317,7 @@ impl CursorVfx for ParticleTrail {
         settings: &CursorSettings,
         canvas: &mut Canvas,
         grid_renderer: &mut GridRenderer,
-        cursor: &Cursor,
+        base_color: Color,
     ) {
         let mut paint = Paint::new(skia_safe::colors::WHITE, None);
         let font_dimensions = grid_renderer.font_dimensions;
@@ -332,9 +329,6 @@ impl CursorVfx for ParticleTrail {
             _ => {}
         }
 
-        let colors = &grid_renderer.default_style.colors;
-        let base_color: Color = cursor.background(colors).to_color();
-
         paint.set_blend_mode(BlendMode::SrcOver);
 
         self.particles.iter().for_each(|particle| {
@@ -3,8 +3,10 @@ mod cursor_vfx;
 
 use std::collections::HashMap;
 
+use csscolorparser::Color;
 use glutin::event::{Event, WindowEvent};
-use skia_safe::{op, Canvas, Paint, Path, Point};
+use log::warn;
+use skia_safe::{op, Canvas, Color4f, Paint, Path, Point};
 
 use crate::{
     bridge::EditorMode,
@@ -28,16 +30,30 @@ pub struct CursorSettings {
     antialiasing: bool,
     animation_length: f32,
     distance_length_adjust: bool,
+
+    /// Upper bound in seconds for the animation length once it has been scaled by the distance
+    /// travelled. A value <=0 leaves it unbounded.
+    animation_max_length: f32,
+
     animate_in_insert_mode: bool,
     animate_command_line: bool,
     trail_size: f32,
 
+    /// Distance in grid cells above which the cursor jumps straight to its destination instead of
+    /// animating, so that large jumps such as `gg` or `G` don't sweep across the whole screen. A
+    /// value <=0 always animates.
+    snap_distance: f32,
+
     /// Specify cursor outline width in ems. You probably want this to be a positive value less
     /// than 0.5. If the value is <=0 then the cursor will be invisible. This setting takes effect
     /// when the editor window is unfocused, at which time a block cursor will be rendered as an
     /// outline instead of as a full rectangle.
     unfocused_outline_width: f32,
 
+    /// A fixed color for the cursor, such as "#ff8800", used instead of the color from the
+    /// cursor highlight. Empty uses the highlight.
+    color: String,
+
     vfx_mode: cursor_vfx::VfxMode,
     vfx_opacity: f32,
     vfx_particle_lifetime: f32,
@@ -53,10 +69,13 @@ impl Default for CursorSettings {
             antialiasing: true,
             animation_length: 0.06,
             distance_length_adjust: true,
+            animation_max_length: 0.0,
             animate_in_insert_mode: true,
             animate_command_line: true,
             trail_size: 0.7,
+            snap_distance: 0.0,
             unfocused_outline_width: 1.0 / 8.0,
+            color: "".to_string(),
             vfx_mode: cursor_vfx::VfxMode::Disabled,
             vfx_opacity: 200.0,
             vfx_particle_lifetime: 1.2,
@@ -74,7 +93,7 @@ pub struct Corner {
     current_position: Point,
     relative_position: Point,
     previous_destination: Point,
-    length_multiplier: f32,
+    animation_length: f32,
     t: f32,
 }
 
@@ -85,7 +104,7 @@ impl Corner {
             current_position: Point::new(0.0, 0.0),
             relative_position: Point::new(0.0, 0.0),
             previous_destination: Point::new(-1000.0, -1000.0),
-            length_multiplier: 1.0,
+            animation_length: 0.0,
             t: 0.0,
         }
     }
@@ -94,6 +113,7 @@ impl Corner {
         &mut self,
         settings: &CursorSettings,
         font_dimensions: Point,
+        cell_dimensions: Point,
         destination: Point,
         dt: f32,
         immediate_movement: bool,
@@ -102,14 +122,10 @@ impl Corner {
             self.t = 0.0;
             self.start_position = self.current_position;
             self.previous_destination = destination;
-            self.length_multiplier = if settings.distance_length_adjust {
-                (destination - self.current_position)
-                    .length()
-                    .log10()
-                    .max(0.0)
-            } else {
-                1.0
-            }
+            self.animation_length = animation_length(
+                settings,
+                grid_distance(destination - self.current_position, cell_dimensions),
+            );
         }
 
         // Check first if animation's over
@@ -159,8 +175,7 @@ impl Corner {
                     (1.0 - settings.trail_size).max(0.0).min(1.0),
                     -direction_alignment,
                 );
-            self.t =
-                (self.t + corner_dt / (settings.animation_length * self.length_multiplier)).min(1.0)
+            self.t = (self.t + corner_dt / self.animation_length).min(1.0)
         }
 
         self.current_position = ease_point(
@@ -180,10 +195,13 @@ pub struct CursorRenderer {
     destination: Point,
     blink_status: BlinkStatus,
     previous_cursor_shape: Option<CursorShape>,
+    previous_cell_percentage: f32,
     previous_editor_mode: EditorMode,
     cursor_vfx: Option<Box<dyn cursor_vfx::CursorVfx>>,
     previous_vfx_mode: cursor_vfx::VfxMode,
     window_has_focus: bool,
+    color_setting: String,
+    color_override: Option<Color4f>,
 }
 
 impl CursorRenderer {
@@ -194,10 +212,13 @@ impl CursorRenderer {
             destination: (0.0, 0.0).into(),
             blink_status: BlinkStatus::new(),
             previous_cursor_shape: None,
+            previous_cell_percentage: DEFAULT_CELL_PERCENTAGE,
             previous_editor_mode: EditorMode::Normal,
             cursor_vfx: None,
             previous_vfx_mode: cursor_vfx::VfxMode::Disabled,
             window_has_focus: true,
+            color_setting: "".to_string(),
+            color_override: None,
         };
         renderer.set_cursor_shape(&CursorShape::Block, DEFAULT_CELL_PERCENTAGE);
         renderer
@@ -213,6 +234,20 @@ impl CursorRenderer {
         }
     }
 
+    /// Returns the top left pixel of the cursor as it is currently drawn, which trails behind the
+    /// destination while the cursor is animating.
+    pub fn animated_position(&self) -> Point {
+        self.corners.iter().fold(
+            Point::new(f32::INFINITY, f32::INFINITY),
+            |position, corner| {
+                Point::new(
+                    position.x.min(corner.current_position.x),
+                    position.y.min(corner.current_position.y),
+                )
+            },
+        )
+    }
+
     pub fn update_cursor(&mut self, new_cursor: Cursor) {
         self.cursor = new_cursor;
     }
@@ -314,15 +349,19 @@ impl CursorRenderer {
 
         let center_destination = self.destination + cursor_dimensions * 0.5;
         let new_cursor = Some(self.cursor.shape.clone());
+        let new_cell_percentage = self
+            .cursor
+            .cell_percentage
+            .unwrap_or(DEFAULT_CELL_PERCENTAGE);
 
-        if self.previous_cursor_shape != new_cursor {
+        // Modes can share a bar shape with different sizes, e.g. ver25 and ver50, so the corners
+        // are also recomputed when only the percentage changes.
+        if self.previous_cursor_shape != new_cursor
+            || (self.previous_cell_percentage - new_cell_percentage).abs() > f32::EPSILON
+        {
             self.previous_cursor_shape = new_cursor.clone();
-            self.set_cursor_shape(
-                &new_cursor.unwrap(),
-                self.cursor
-                    .cell_percentage
-                    .unwrap_or(DEFAULT_CELL_PERCENTAGE),
-            );
+            self.previous_cell_percentage = new_cell_percentage;
+            self.set_cursor_shape(&new_cursor.unwrap(), new_cell_percentage);
 
             if let Some(vfx) = self.cursor_vfx.as_mut() {
                 vfx.restart(center_destination);
@@ -331,14 +370,28 @@ impl CursorRenderer {
 
         let mut animating = false;
 
+        let cell_dimensions: Point = (
+            grid_renderer.font_dimensions.width as f32,
+            grid_renderer.font_dimensions.height as f32,
+        )
+            .into();
+
+        let snap_to_destination = settings.snap_distance > 0.0
+            && grid_distance(
+                center_destination - self.corners[0].previous_destination,
+                cell_dimensions,
+            ) > settings.snap_distance;
+
         if !center_destination.is_zero() {
             for corner in self.corners.iter_mut() {
                 let immediate_movement = !settings.animate_in_insert_mode && in_insert_mode
-                    || !settings.animate_command_line && !changed_to_from_cmdline;
+                    || !settings.animate_command_line && !changed_to_from_cmdline
+                    || snap_to_destination;
 
                 let corner_animating = corner.update(
                     &settings,
                     cursor_dimensions,
+                    cell_dimensions,
                     center_destination,
                     dt,
                     immediate_movement,
@@ -364,12 +417,19 @@ impl CursorRenderer {
         if !(self.cursor.enabled && render) {
             return;
         }
+        // Only parse the color override when the setting changes, so that a malformed value is
+        // reported once instead of every frame.
+        if settings.color != self.color_setting {
+            self.color_setting = settings.color.clone();
+            self.color_override = parse_cursor_color(&settings.color);
+        }
+
         // Draw Background
-        let background_color = self
-            .cursor
-            .background(&grid_renderer.default_style.colors)
-            .to_color()
-            .with_a(self.cursor.alpha());
+        let cursor_color = self
+            .color_override
+            .unwrap_or_else(|| self.cursor.background(&grid_renderer.default_style.colors))
+            .to_color();
+        let background_color = cursor_color.with_a(self.cursor.alpha());
         paint.set_color(background_color);
 
         let path = if self.window_has_focus || self.cursor.shape != CursorShape::Block {
@@ -390,13 +450,12 @@ impl CursorRenderer {
         canvas.save();
         canvas.clip_path(&path, None, Some(false));
 
-        let y_adjustment = grid_renderer.shaper.y_adjustment();
-        let style = &self.cursor.grid_cell.1;
+        let y_adjustment = grid_renderer.baseline_offset();
+        let style = grid_renderer.resolve_style(&self.cursor.grid_cell.1);
 
-        let bold = style.as_ref().map(|x| x.bold).unwrap_or(false);
-        let italic = style.as_ref().map(|x| x.italic).unwrap_or(false);
-
-        let blobs = &grid_renderer.shaper.shape_cached(character, bold, italic);
+        let blobs = &grid_renderer
+            .shaper
+            .shape_cached(character, style.bold, style.italic);
 
         for blob in blobs.iter() {
             canvas.draw_text_blob(
@@ -409,7 +468,8 @@ impl CursorRenderer {
         canvas.restore();
 
         if let Some(vfx) = self.cursor_vfx.as_ref() {
-            vfx.render(&settings, canvas, grid_renderer, &self.cursor);
+            // The effects use the cursor color too, so that they follow the color override
+            vfx.render(&settings, canvas, grid_renderer, cursor_color);
         }
     }
 
@@ -463,3 +523,83 @@ impl CursorRenderer {
         path
     }
 }
+
+/// Length of a movement in grid cells rather than pixels, so that animations feel the same
+/// regardless of the font size.
+fn grid_distance(delta: Point, cell_dimensions: Point) -> f32 {
+    Point::new(delta.x / cell_dimensions.x, delta.y / cell_dimensions.y).length()
+}
+
+/// Seconds an animation covering `distance` grid cells should take. With distance_length_adjust
+/// set, every tenfold increase in distance adds another animation_length, capped by
+/// animation_max_length.
+fn animation_length(settings: &CursorSettings, distance: f32) -> f32 {
+    let multiplier = if settings.distance_length_adjust {
+        1.0 + distance.max(1.0).log10()
+    } else {
+        1.0
+    };
+    let length = settings.animation_length * multiplier;
+
+    if settings.animation_max_length > 0.0 {
+        length.min(settings.animation_max_length)
+    } else {
+        length
+    }
+}
+
+fn parse_cursor_color(color: &str) -> Option<Color4f> {
+    if color.is_empty() {
+        return None;
+    }
+
+    match color.parse::<Color>() {
+        Ok(color) => {
+            let [red, green, blue, alpha] = color.to_array();
+            Some(Color4f::new(
+                red as f32,
+                green as f32,
+                blue as f32,
+                alpha as f32,
+            ))
+        }
+        Err(_) => {
+            warn!("Ignoring invalid cursor color {}", color);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_cursor_color_accepts_hex_and_ignores_malformed_values() {
+        assert_eq!(
+            parse_cursor_color("#ff0000"),
+            Some(Color4f::new(1.0, 0.0, 0.0, 1.0))
+        );
+        assert_eq!(parse_cursor_color(""), None);
+        assert_eq!(parse_cursor_color("not a color"), None);
+    }
+
+    #[test]
+    fn animation_length_scales_with_grid_distance_up_to_the_max() {
+        let mut settings = CursorSettings {
+            animation_length: 0.1,
+            ..Default::default()
+        };
+        let cell = Point::new(10.0, 20.0);
+
+        assert_eq!(grid_distance(Point::new(30.0, 80.0), cell), 5.0);
+        assert!((animation_length(&settings, 1.0) - 0.1).abs() < f32::EPSILON);
+        assert!((animation_length(&settings, 100.0) - 0.3).abs() < 1e-6);
+
+        settings.animation_max_length = 0.15;
+        assert!((animation_length(&settings, 100.0) - 0.15).abs() < f32::EPSILON);
+
+        settings.distance_length_adjust = false;
+        assert!((animation_length(&settings, 100.0) - 0.1).abs() < f32::EPSILON);
+    }
+}
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use log::{debug, trace, warn};
+use log::{debug, warn};
 use lru::LruCache;
 use skia_safe::{
     graphics::{font_cache_limit, font_cache_used, set_font_cache_limit},
@@ -154,7 +154,7 @@ impl CachingShaper {
     pub fn font_base_dimensions(&mut self) -> (u64, u64) {
         let (metrics, glyph_advance) = self.info();
         let font_height = (metrics.ascent + metrics.descent + metrics.leading).ceil() as u64;
-        let font_width = (glyph_advance + 0.5).floor() as u64;
+        let font_width = self.options.cell_width(glyph_advance, self.scale_factor);
 
         (font_width, font_height)
     }
@@ -168,6 +168,12 @@ impl CachingShaper {
         (metrics.ascent + metrics.leading).ceil() as u64
     }
 
+    // Offset from the top of the cell to the middle of the x-height, where strikethrough is drawn.
+    pub fn strikethrough_position(&mut self) -> f32 {
+        let metrics = self.metrics();
+        self.y_adjustment() as f32 - metrics.x_height / 2.0
+    }
+
     fn build_clusters(
         &mut self,
         text: &str,
@@ -318,7 +324,7 @@ impl CachingShaper {
 
         let mut resulting_blobs = Vec::new();
 
-        trace!("Shaping text: {}", text);
+        trace_sampled!("Shaping text: {}", text);
 
         for (cluster_group, font_pair) in self.build_clusters(&text, bold, italic) {
             let mut shaper = self
@@ -6,6 +6,7 @@ const DEFAULT_FONT_SIZE: f32 = 14.0;
 pub struct FontOptions {
     pub font_list: Vec<String>,
     pub size: f32,
+    pub width: Option<f32>,
     pub bold: bool,
     pub italic: bool,
     pub allow_float_size: bool,
@@ -17,6 +18,7 @@ impl FontOptions {
     pub fn parse(guifont_setting: &str) -> FontOptions {
         let mut font_list = Vec::new();
         let mut size = DEFAULT_FONT_SIZE;
+        let mut width = None;
         let mut bold = false;
         let mut italic = false;
         let mut allow_float_size = false;
@@ -28,6 +30,7 @@ impl FontOptions {
         if let Some(parts) = parts.next() {
             let parsed_font_list: Vec<String> = parts
                 .split(',')
+                .map(|fallback| fallback.trim())
                 .filter(|fallback| !fallback.is_empty())
                 .map(parse_font_name)
                 .collect();
@@ -49,6 +52,10 @@ impl FontOptions {
                 if let Ok(parsed_size) = part[1..].parse::<f32>() {
                     size = parsed_size
                 }
+            } else if part.starts_with('w') && part.len() > 1 {
+                if let Ok(parsed_width) = part[1..].parse::<f32>() {
+                    width = Some(points_to_pixels(parsed_width))
+                }
             } else if part == "b" {
                 bold = true;
             } else if part == "i" {
@@ -64,12 +71,22 @@ impl FontOptions {
             hinting,
             edging,
             size: points_to_pixels(size),
+            width,
         }
     }
 
     pub fn primary_font(&self) -> Option<String> {
         self.font_list.first().cloned()
     }
+
+    /// Width of a grid cell in pixels. The `w` option overrides the advance of the font, which
+    /// spreads the glyphs out or packs them closer together.
+    pub fn cell_width(&self, glyph_advance: f32, scale_factor: f32) -> u64 {
+        let width = self
+            .width
+            .map_or(glyph_advance, |width| width * scale_factor);
+        ((width + 0.5).floor() as u64).max(1)
+    }
 }
 
 impl Default for FontOptions {
@@ -80,6 +97,7 @@ impl Default for FontOptions {
             italic: false,
             allow_float_size: false,
             size: points_to_pixels(DEFAULT_FONT_SIZE),
+            width: None,
             hinting: FontHinting::default(),
             edging: FontEdging::default(),
         }
@@ -90,6 +108,7 @@ impl PartialEq for FontOptions {
     fn eq(&self, other: &Self) -> bool {
         self.font_list == other.font_list
             && (self.size - other.size).abs() < std::f32::EPSILON
+            && self.width == other.width
             && self.bold == other.bold
             && self.italic == other.italic
             && self.edging == other.edging
@@ -215,6 +234,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_many_fonts_with_whitespace_from_guifont_setting() {
+        let guifont_setting = "Fira Code Mono, Noto Color Emoji ,:h14";
+        let font_options = FontOptions::parse(guifont_setting);
+
+        assert_eq!(
+            font_options.font_list,
+            vec!["Fira Code Mono".to_string(), "Noto Color Emoji".to_string()],
+            "font list should equal {:?}, but {:?}",
+            vec!["Fira Code Mono", "Noto Color Emoji"],
+            font_options.font_list,
+        );
+    }
+
     #[test]
     fn test_parse_edging_from_guifont_setting() {
         let guifont_setting = "Fira Code Mono:#e-subpixelantialias";
@@ -297,6 +330,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_width_and_ignore_unknown_flags_from_guifont_setting() {
+        let guifont_setting = "Fira Code Mono:h15:w8:x:b";
+        let font_options = FontOptions::parse(guifont_setting);
+
+        let width_pixels = points_to_pixels(8.0);
+        assert_eq!(
+            font_options.width,
+            Some(width_pixels),
+            "font width should equal {:?}, but {:?}",
+            Some(width_pixels),
+            font_options.width,
+        );
+
+        assert!(
+            font_options.bold,
+            "bold should be set after an unknown flag"
+        );
+    }
+
+    #[test]
+    fn test_cell_width_uses_the_width_option_over_the_advance() {
+        let mut font_options = FontOptions::parse("Fira Code Mono:h15");
+        assert_eq!(font_options.cell_width(8.4, 2.0), 8);
+
+        font_options.width = Some(10.0);
+        assert_eq!(font_options.cell_width(8.4, 2.0), 20);
+
+        font_options.width = Some(0.0);
+        assert_eq!(font_options.cell_width(8.4, 1.0), 1);
+    }
+
     #[test]
     fn test_parse_font_name_with_escapes() {
         let without_escapes_or_specials_chars = parse_font_name("Fira Code Mono");
@@ -3,8 +3,9 @@ use std::sync::Arc;
 use glutin::dpi::PhysicalSize;
 use log::trace;
 use skia_safe::{
-    colors, dash_path_effect, BlendMode, Canvas, Color, Paint, Path, Point, Rect, HSV,
+    colors, dash_path_effect, BlendMode, Canvas, Color, Paint, PaintStyle, Path, Point, Rect, HSV,
 };
+use unicode_segmentation::UnicodeSegmentation;
 
 use crate::{
     dimensions::Dimensions,
@@ -14,6 +15,14 @@ use crate::{
     window::WindowSettings,
 };
 
+// Number of cells on either side of a fragment that its text may draw into. Powerline separators
+// and Nerd Font icons often overhang their cell, so this needs to be wide enough for them not to be
+// cut off.
+const GLYPH_OVERHANG_CELLS: u64 = 1;
+
+// Opacity of the debug fill character relative to the text, so that it stays in the background.
+const FILL_CHARACTER_ALPHA: f32 = 0.3;
+
 pub struct GridRenderer {
     pub shaper: CachingShaper,
     pub paint: Paint,
@@ -22,6 +31,12 @@ pub struct GridRenderer {
     pub font_dimensions: Dimensions,
     pub scale_factor: f64,
     pub is_ready: bool,
+    // Extra pixels between rows from 'linespace', split evenly above and below the text
+    pub linespace: i64,
+    // Rows and columns of the window taken up by gui elements drawn outside of the grid, such as a
+    // tabline, which Neovim shouldn't draw into
+    pub reserved_rows: u64,
+    pub reserved_cols: u64,
 }
 
 impl GridRenderer {
@@ -29,10 +44,11 @@ impl GridRenderer {
         let mut shaper = CachingShaper::new(scale_factor as f32);
         let mut paint = Paint::new(colors::WHITE, None);
         paint.set_anti_alias(false);
+        // The special color falls back to the foreground until Neovim sends one.
         let default_style = Arc::new(Style::new(Colors::new(
             Some(colors::WHITE),
             Some(colors::BLACK),
-            Some(colors::GREY),
+            None,
         )));
         let em_size = shaper.current_size();
         let font_dimensions: Dimensions = shaper.font_base_dimensions().into();
@@ -45,6 +61,9 @@ impl GridRenderer {
             font_dimensions,
             scale_factor,
             is_ready: false,
+            linespace: 0,
+            reserved_rows: 0,
+            reserved_cols: 0,
         }
     }
 
@@ -52,14 +71,19 @@ impl GridRenderer {
         self.shaper.font_names()
     }
 
-    /// Convert PhysicalSize to grid size.
+    /// Convert PhysicalSize to grid size, leaving out the reserved rows and columns. The grid is
+    /// never smaller than a single cell.
     pub fn convert_physical_to_grid(&self, physical: PhysicalSize<u32>) -> Dimensions {
-        Dimensions::from(physical) / self.font_dimensions
+        physical_to_grid(physical.into(), self.font_dimensions, self.reserved_cells())
     }
 
-    /// Convert grid size to PhysicalSize.
+    /// Convert grid size to PhysicalSize, making room for the reserved rows and columns.
     pub fn convert_grid_to_physical(&self, grid: Dimensions) -> PhysicalSize<u32> {
-        (grid * self.font_dimensions).into()
+        grid_to_physical(grid, self.font_dimensions, self.reserved_cells()).into()
+    }
+
+    fn reserved_cells(&self) -> Dimensions {
+        Dimensions::from((self.reserved_cols, self.reserved_rows))
     }
 
     pub fn handle_scale_factor_update(&mut self, scale_factor: f64) {
@@ -72,18 +96,39 @@ impl GridRenderer {
         self.update_font_dimensions();
     }
 
+    pub fn update_linespace(&mut self, linespace: i64) {
+        self.linespace = linespace;
+        self.update_font_dimensions();
+    }
+
     fn update_font_dimensions(&mut self) {
         self.em_size = self.shaper.current_size();
-        self.font_dimensions = self.shaper.font_base_dimensions().into();
+        let mut font_dimensions: Dimensions = self.shaper.font_base_dimensions().into();
+        font_dimensions.height = (font_dimensions.height as i64 + self.linespace).max(1) as u64;
+        self.font_dimensions = font_dimensions;
         self.is_ready = true;
         trace!("Updated font dimensions: {:?}", self.font_dimensions,);
     }
 
+    /// Cells without a style are drawn with the default style.
+    pub fn resolve_style(&self, style: &Option<Arc<Style>>) -> Arc<Style> {
+        style.clone().unwrap_or_else(|| self.default_style.clone())
+    }
+
+    /// Vertical offset of the text baseline from the top of a cell, including the half of the
+    /// linespace that goes above the text.
+    pub fn baseline_offset(&mut self) -> u64 {
+        (self.shaper.y_adjustment() as i64 + self.linespace / 2).max(0) as u64
+    }
+
+    /// Pixel rect of a region of the grid `grid_size` cells wide and tall at the current cell
+    /// size, which already includes the linespace.
+    pub fn compute_grid_region(&self, grid_position: (u64, u64), grid_size: (u64, u64)) -> Rect {
+        grid_region(self.font_dimensions, grid_position, grid_size)
+    }
+
     fn compute_text_region(&self, grid_position: (u64, u64), cell_width: u64) -> Rect {
-        let (x, y) = grid_position * self.font_dimensions;
-        let width = cell_width * self.font_dimensions.width;
-        let height = self.font_dimensions.height;
-        Rect::new(x as f32, y as f32, (x + width) as f32, (y + height) as f32)
+        self.compute_grid_region(grid_position, (cell_width, 1))
     }
 
     pub fn get_default_background(&self) -> Color {
@@ -101,9 +146,10 @@ impl GridRenderer {
         self.paint.set_blend_mode(BlendMode::Src);
 
         let region = self.compute_text_region(grid_position, cell_width);
-        let style = style.as_ref().unwrap_or(&self.default_style);
+        let style = self.resolve_style(style);
+        let debug_renderer = SETTINGS.get::<RendererSettings>().debug_renderer;
 
-        if SETTINGS.get::<RendererSettings>().debug_renderer {
+        if debug_renderer {
             let random_hsv: HSV = (rand::random::<f32>() * 360.0, 0.3, 0.3).into();
             let random_color = random_hsv.to_color(255);
             self.paint.set_color(random_color);
@@ -122,6 +168,14 @@ impl GridRenderer {
             self.paint.set_alpha(0);
         }
         canvas.draw_rect(region, &self.paint);
+
+        // Outline every fragment so that it is visible how finely the lines got split up
+        if debug_renderer {
+            let mut outline_paint = Paint::new(colors::WHITE, None);
+            outline_paint.set_style(PaintStyle::Stroke);
+            outline_paint.set_stroke_width(1.0);
+            canvas.draw_rect(region, &outline_paint);
+        }
     }
 
     pub fn draw_foreground(
@@ -131,38 +185,51 @@ impl GridRenderer {
         grid_position: (u64, u64),
         cell_width: u64,
         style: &Option<Arc<Style>>,
+        blank_cells: &[u64],
     ) {
-        let (x, y) = grid_position * self.font_dimensions;
-        let width = cell_width * self.font_dimensions.width;
+        let settings = SETTINGS.get::<RendererSettings>();
+        let cell_region = self.compute_text_region(grid_position, cell_width);
+        let (x, y) = (cell_region.left as u64, cell_region.top as u64);
+        let width = cell_region.width() as u64;
 
-        let style = style.as_ref().unwrap_or(&self.default_style);
+        let style = self.resolve_style(style);
 
-        // We don't want to clip text in the x position, only the y so we add a buffer of 1
-        // character on either side of the region so that we clip vertically but not horizontally.
-        let (grid_x, grid_y) = grid_position;
-        let clip_position = (grid_x.saturating_sub(1), grid_y);
-        let region = self.compute_text_region(clip_position, cell_width + 2);
+        let text = if settings.show_control_characters {
+            show_control_characters(text)
+        } else {
+            text
+        };
 
-        if let Some(underline_style) = style.underline {
-            let line_position = self.shaper.underline_position();
-            let p1 = (
-                x as f32,
-                (y - line_position + self.font_dimensions.height) as f32,
-            );
-            let p2 = (
-                (x + width) as f32,
-                (y - line_position + self.font_dimensions.height) as f32,
-            );
+        // We don't want to clip text in the x position, only the y so we add a buffer of a few
+        // characters on either side of the region so that we clip vertically but not horizontally.
+        let (clip_position, clip_width) = text_clip_cells(grid_position, cell_width);
+        let region = self.compute_text_region(clip_position, clip_width);
 
-            self.draw_underline(canvas, style, underline_style, p1.into(), p2.into())
+        if let Some(underline_style) = style.underline {
+            // The underline position is relative to the bottom of the text, which sits above the
+            // half of the linespace that goes below it
+            let line_position = self.shaper.underline_position() as i64;
+            let text_bottom =
+                (y + self.font_dimensions.height) as i64 - (self.linespace - self.linespace / 2);
+            let p1 = (x as f32, (text_bottom - line_position) as f32);
+            let p2 = ((x + width) as f32, (text_bottom - line_position) as f32);
+
+            self.draw_underline(
+                canvas,
+                &settings,
+                &style,
+                underline_style,
+                p1.into(),
+                p2.into(),
+            )
         }
 
         canvas.save();
         canvas.clip_rect(region, None, Some(false));
 
-        let y_adjustment = self.shaper.y_adjustment();
+        let y_adjustment = self.baseline_offset();
 
-        if SETTINGS.get::<RendererSettings>().debug_renderer {
+        if settings.debug_renderer {
             let random_hsv: HSV = (rand::random::<f32>() * 360.0, 1.0, 1.0).into();
             let random_color = random_hsv.to_color(255);
             self.paint.set_color(random_color);
@@ -172,16 +239,54 @@ impl GridRenderer {
         }
         self.paint.set_anti_alias(false);
 
-        for blob in self
-            .shaper
-            .shape_cached(text, style.bold, style.italic)
-            .iter()
-        {
-            canvas.draw_text_blob(blob, (x as f32, (y + y_adjustment) as f32), &self.paint);
+        if settings.ligatures {
+            for blob in self
+                .shaper
+                .shape_cached(text, style.bold, style.italic)
+                .iter()
+            {
+                canvas.draw_text_blob(blob, (x as f32, (y + y_adjustment) as f32), &self.paint);
+            }
+        } else {
+            // Shape every cell on its own so that no ligatures are formed across cells.
+            for (index, grapheme) in text.graphemes(true).enumerate() {
+                let grapheme_x = x + index as u64 * self.font_dimensions.width;
+                for blob in self
+                    .shaper
+                    .shape_cached(grapheme.to_owned(), style.bold, style.italic)
+                    .iter()
+                {
+                    canvas.draw_text_blob(
+                        blob,
+                        (grapheme_x as f32, (y + y_adjustment) as f32),
+                        &self.paint,
+                    );
+                }
+            }
+        }
+
+        if let Some(fill) = fill_character(&settings.debug_fill_character) {
+            if !blank_cells.is_empty() {
+                let mut color = style.foreground(&self.default_style.colors);
+                color.a *= FILL_CHARACTER_ALPHA;
+                self.paint.set_color(color.to_color());
+
+                for blob in self.shaper.shape_cached(fill, false, false).iter() {
+                    for cell in blank_cells {
+                        let cell_x = x + cell * self.font_dimensions.width;
+                        canvas.draw_text_blob(
+                            blob,
+                            (cell_x as f32, (y + y_adjustment) as f32),
+                            &self.paint,
+                        );
+                    }
+                }
+            }
         }
 
         if style.strikethrough {
-            let line_position = region.center_y();
+            let line_position =
+                y as f32 + self.shaper.strikethrough_position() + (self.linespace / 2) as f32;
             self.paint
                 .set_color(style.special(&self.default_style.colors).to_color());
             canvas.draw_line(
@@ -197,6 +302,7 @@ impl GridRenderer {
     fn draw_underline(
         &self,
         canvas: &mut Canvas,
+        settings: &RendererSettings,
         style: &Arc<Style>,
         underline_style: UnderlineStyle,
         p1: Point,
@@ -204,10 +310,15 @@ impl GridRenderer {
     ) {
         canvas.save();
 
+        // Clip horizontally to the fragment so that the decoration stays continuous across the
+        // run without spilling into the neighbouring cells. The undercurl in particular steps in
+        // half cell increments and would otherwise overshoot the end of the run.
+        let font_height = self.font_dimensions.height as f32;
+        let clip_region = Rect::new(p1.x, p1.y - font_height, p2.x, p2.y + font_height);
+        canvas.clip_rect(clip_region, None, Some(false));
+
         let mut underline_paint = self.paint.clone();
-        let auto_scaling = SETTINGS
-            .get::<RendererSettings>()
-            .underline_automatic_scaling;
+        let auto_scaling = settings.underline_automatic_scaling;
         // Arbitrary value under which we simply round the line thickness to 1. Anything else
         // results in ugly aliasing artifacts.
         let stroke_width = if self.shaper.current_size() < 15. || !auto_scaling {
@@ -272,3 +383,142 @@ impl GridRenderer {
         canvas.restore();
     }
 }
+
+// Control characters would otherwise shape to blank or missing glyphs, so they are replaced with
+// their symbol from the Unicode Control Pictures block, which also occupies a single cell.
+fn show_control_characters(text: String) -> String {
+    fn control_picture(character: char) -> char {
+        match character {
+            '\u{0}'..='\u{1f}' => char::from_u32(0x2400 + character as u32).unwrap_or(character),
+            '\u{7f}' => '\u{2421}',
+            _ => character,
+        }
+    }
+
+    if text.chars().any(|character| character.is_ascii_control()) {
+        text.chars().map(control_picture).collect()
+    } else {
+        text
+    }
+}
+
+// The first character of the debug fill setting is drawn faintly in every blank cell, so that gaps
+// Neovim never drew to show up while debugging. The grid itself keeps the spaces.
+fn fill_character(fill: &str) -> Option<String> {
+    fill.chars().next().map(String::from)
+}
+
+fn grid_region(
+    font_dimensions: Dimensions,
+    grid_position: (u64, u64),
+    (width, height): (u64, u64),
+) -> Rect {
+    let (x, y) = grid_position * font_dimensions;
+    let (width, height) = (width, height) * font_dimensions;
+    Rect::from_xywh(x as f32, y as f32, width as f32, height as f32)
+}
+
+// The reserved cells are taken off the cells that fit in the window, keeping at least one for the
+// grid.
+fn physical_to_grid(
+    physical: Dimensions,
+    font_dimensions: Dimensions,
+    reserved: Dimensions,
+) -> Dimensions {
+    let (cells, _) = physical.fit_cells(font_dimensions);
+    Dimensions::from((
+        cells.width.saturating_sub(reserved.width).max(1),
+        cells.height.saturating_sub(reserved.height).max(1),
+    ))
+}
+
+// Size in pixels of the grid together with the reserved cells around it.
+fn grid_to_physical(
+    grid: Dimensions,
+    font_dimensions: Dimensions,
+    reserved: Dimensions,
+) -> Dimensions {
+    Dimensions::from((grid.width + reserved.width, grid.height + reserved.height)) * font_dimensions
+}
+
+// Returns the first cell and width in cells of the region text is clipped to.
+fn text_clip_cells((grid_x, grid_y): (u64, u64), cell_width: u64) -> ((u64, u64), u64) {
+    let clip_x = grid_x.saturating_sub(GLYPH_OVERHANG_CELLS);
+    ((clip_x, grid_y), cell_width + 2 * GLYPH_OVERHANG_CELLS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_clip_region_extends_past_the_fragment() {
+        assert_eq!(
+            text_clip_cells((5, 2), 3),
+            ((5 - GLYPH_OVERHANG_CELLS, 2), 3 + 2 * GLYPH_OVERHANG_CELLS)
+        );
+        // The region can't start left of the grid
+        assert_eq!(text_clip_cells((0, 0), 3).0, (0, 0));
+    }
+
+    #[test]
+    fn grid_region_covers_whole_cells() {
+        let font_dimensions = Dimensions::from((10u64, 20u64));
+
+        assert_eq!(
+            grid_region(font_dimensions, (2, 3), (4, 1)),
+            Rect::from_xywh(20.0, 60.0, 40.0, 20.0)
+        );
+        assert_eq!(
+            grid_region(font_dimensions, (0, 1), (5, 2)),
+            Rect::from_xywh(0.0, 20.0, 50.0, 40.0)
+        );
+    }
+
+    #[test]
+    fn size_conversions_account_for_the_reserved_cells() {
+        let font_dimensions = Dimensions::from((10u64, 20u64));
+        let physical = Dimensions::from((805u64, 500u64));
+
+        let no_reserved = Dimensions::from((0u64, 0u64));
+        assert_eq!(
+            physical_to_grid(physical, font_dimensions, no_reserved),
+            Dimensions::from((80u64, 25u64))
+        );
+
+        let reserved = Dimensions::from((2u64, 1u64));
+        let grid = physical_to_grid(physical, font_dimensions, reserved);
+        assert_eq!(grid, Dimensions::from((78u64, 24u64)));
+        assert_eq!(
+            grid_to_physical(grid, font_dimensions, reserved),
+            Dimensions::from((800u64, 500u64))
+        );
+
+        // Reserving more than fits still leaves a single cell for the grid
+        let too_many = Dimensions::from((100u64, 100u64));
+        assert_eq!(
+            physical_to_grid(physical, font_dimensions, too_many),
+            Dimensions::from((1u64, 1u64))
+        );
+    }
+
+    #[test]
+    fn control_characters_are_replaced_with_control_pictures() {
+        assert_eq!(show_control_characters("a\tb".to_owned()), "a\u{2409}b");
+        assert_eq!(
+            show_control_characters("\u{0}\u{7f}".to_owned()),
+            "\u{2400}\u{2421}"
+        );
+        assert_eq!(
+            show_control_characters("plain text".to_owned()),
+            "plain text"
+        );
+    }
+
+    #[test]
+    fn blank_cells_are_filled_only_when_a_fill_character_is_set() {
+        assert_eq!(fill_character("\u{b7}"), Some("\u{b7}".to_owned()));
+        assert_eq!(fill_character(".-"), Some(".".to_owned()));
+        assert_eq!(fill_character(""), None);
+    }
+}
@@ -9,17 +9,20 @@ use std::{
     cmp::Ordering,
     collections::{hash_map::Entry, HashMap},
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 use glutin::event::Event;
 use log::error;
-use skia_safe::Canvas;
+use skia_safe::{BlendMode, Canvas, Color, Paint, Point};
 use tokio::sync::mpsc::UnboundedReceiver;
 
 use crate::{
     bridge::EditorMode,
+    dimensions::Dimensions,
     editor::{Cursor, Style},
     event_aggregator::EVENT_AGGREGATOR,
+    redraw_scheduler::REDRAW_SCHEDULER,
     settings::*,
     WindowSettings,
 };
@@ -31,6 +34,9 @@ pub use rendered_window::{
     LineFragment, RenderedWindow, WindowDrawCommand, WindowDrawDetails, WindowPadding,
 };
 
+// How long the screen stays inverted after a visual bell.
+const VISUAL_BELL_LENGTH: Duration = Duration::from_millis(100);
+
 #[derive(SettingGroup, Clone)]
 pub struct RendererSettings {
     position_animation_length: f32,
@@ -42,6 +48,9 @@ pub struct RendererSettings {
     debug_renderer: bool,
     profiler: bool,
     underline_automatic_scaling: bool,
+    ligatures: bool,
+    show_control_characters: bool,
+    debug_fill_character: String,
 }
 
 impl Default for RendererSettings {
@@ -56,6 +65,9 @@ impl Default for RendererSettings {
             debug_renderer: false,
             profiler: false,
             underline_automatic_scaling: false,
+            ligatures: true,
+            show_control_characters: true,
+            debug_fill_character: "".to_string(),
         }
     }
 }
@@ -69,8 +81,10 @@ pub enum DrawCommand {
     },
     UpdateCursor(Cursor),
     FontChanged(String),
+    LineSpaceChanged(i64),
     DefaultStyleChanged(Style),
     ModeChanged(EditorMode),
+    VisualBell,
 }
 
 pub struct Renderer {
@@ -82,10 +96,13 @@ pub struct Renderer {
     pub window_regions: Vec<WindowDrawDetails>,
 
     pub batched_draw_command_receiver: UnboundedReceiver<Vec<DrawCommand>>,
+    /// Kept between frames so that collecting the batches of each frame reuses its allocation.
+    draw_commands: Vec<DrawCommand>,
     profiler: profiler::Profiler,
     os_scale_factor: f64,
     user_scale_factor: f64,
     pub window_padding: WindowPadding,
+    visual_bell_until: Option<Instant>,
 }
 
 impl Renderer {
@@ -118,10 +135,12 @@ impl Renderer {
             current_mode,
             window_regions,
             batched_draw_command_receiver,
+            draw_commands: Vec::new(),
             profiler,
             os_scale_factor,
             user_scale_factor,
             window_padding,
+            visual_bell_until: None,
         }
     }
 
@@ -139,25 +158,28 @@ impl Renderer {
     /// `bool` indicating whether or not font was changed during this frame.
     #[allow(clippy::needless_collect)]
     pub fn draw_frame(&mut self, root_canvas: &mut Canvas, dt: f32) -> bool {
-        let mut draw_commands = Vec::new();
+        let mut draw_commands = std::mem::take(&mut self.draw_commands);
         while let Ok(draw_command) = self.batched_draw_command_receiver.try_recv() {
             draw_commands.extend(draw_command);
         }
 
         let mut font_changed = false;
 
-        for draw_command in draw_commands.into_iter() {
-            if let DrawCommand::FontChanged(_) = draw_command {
+        for draw_command in draw_commands.drain(..) {
+            if let DrawCommand::FontChanged(_) | DrawCommand::LineSpaceChanged(_) = draw_command {
                 font_changed = true;
             }
             self.handle_draw_command(root_canvas, draw_command);
         }
+        self.draw_commands = draw_commands;
 
         let default_background = self.grid_renderer.get_default_background();
         let font_dimensions = self.grid_renderer.font_dimensions;
 
+        // Only the background is made translucent, text is always drawn fully opaque.
         let transparency = { SETTINGS.get::<WindowSettings>().transparency };
-        root_canvas.clear(default_background.with_a((255.0 * transparency) as u8));
+        let background_alpha = (255.0 * transparency.clamp(0.0, 1.0)) as u8;
+        root_canvas.clear(default_background.with_a(background_alpha));
         root_canvas.save();
         root_canvas.reset_matrix();
 
@@ -206,7 +228,7 @@ impl Renderer {
                 window.draw(
                     root_canvas,
                     &settings,
-                    default_background.with_a((255.0 * transparency) as u8),
+                    default_background.with_a(background_alpha),
                     font_dimensions,
                     dt,
                 )
@@ -220,6 +242,8 @@ impl Renderer {
         self.cursor_renderer
             .draw(&mut self.grid_renderer, &self.current_mode, root_canvas, dt);
 
+        self.draw_visual_bell(root_canvas);
+
         self.profiler.draw(root_canvas, dt);
 
         root_canvas.restore();
@@ -227,6 +251,44 @@ impl Renderer {
         font_changed
     }
 
+    // Inverts everything drawn so far while the visual bell is active. Frames keep getting queued
+    // until it ends, so the screen is drawn normally again right after.
+    fn draw_visual_bell(&mut self, root_canvas: &mut Canvas) {
+        if let Some(visual_bell_until) = self.visual_bell_until {
+            if Instant::now() < visual_bell_until {
+                let mut paint = Paint::default();
+                paint.set_color(Color::WHITE);
+                paint.set_blend_mode(BlendMode::Difference);
+                root_canvas.draw_paint(&paint);
+            } else {
+                self.visual_bell_until = None;
+            }
+            REDRAW_SCHEDULER.queue_next_frame();
+        }
+    }
+
+    /// Returns where the cursor is drawn this frame, for placing things like the IME candidate
+    /// window next to it.
+    pub fn cursor_pixel_position(&self) -> Point {
+        self.cursor_renderer.animated_position()
+    }
+
+    /// Returns the topmost window drawn under the given pixel position.
+    pub fn window_details_at(&self, x: f32, y: f32) -> Option<&WindowDrawDetails> {
+        window_details_at(&self.window_regions, x, y)
+    }
+
+    /// Maps a pixel position to the grid drawn on top at that point and the (column, row) of the
+    /// cell within that grid. Returns None outside of every grid.
+    pub fn grid_at_pixel(&self, x: f32, y: f32) -> Option<(u64, (u64, u64))> {
+        grid_at_pixel(
+            &self.window_regions,
+            self.grid_renderer.font_dimensions,
+            x,
+            y,
+        )
+    }
+
     pub fn handle_os_scale_factor_change(&mut self, os_scale_factor: f64) {
         self.os_scale_factor = os_scale_factor;
         self.grid_renderer
@@ -276,12 +338,22 @@ impl Renderer {
             DrawCommand::FontChanged(new_font) => {
                 self.grid_renderer.update_font(&new_font);
             }
-            DrawCommand::DefaultStyleChanged(new_style) => {
+            DrawCommand::LineSpaceChanged(linespace) => {
+                self.grid_renderer.update_linespace(linespace);
+            }
+            DrawCommand::DefaultStyleChanged(mut new_style) => {
+                // Colors that Neovim didn't set keep their previous value
+                new_style.colors = new_style
+                    .colors
+                    .with_fallback(&self.grid_renderer.default_style.colors);
                 self.grid_renderer.default_style = Arc::new(new_style);
             }
             DrawCommand::ModeChanged(new_mode) => {
                 self.current_mode = new_mode;
             }
+            DrawCommand::VisualBell => {
+                self.visual_bell_until = Some(Instant::now() + VISUAL_BELL_LENGTH);
+            }
             _ => {}
         }
     }
@@ -313,3 +385,83 @@ fn floating_sort(window_a: &&mut RenderedWindow, window_b: &&mut RenderedWindow)
     }
     ord
 }
+
+fn window_details_at(
+    window_regions: &[WindowDrawDetails],
+    x: f32,
+    y: f32,
+) -> Option<&WindowDrawDetails> {
+    // The rendered window regions are sorted by draw order, so the earlier windows in the list are
+    // drawn under the later ones
+    window_regions
+        .iter()
+        .filter(|details| {
+            x >= details.region.left
+                && x < details.region.right
+                && y >= details.region.top
+                && y < details.region.bottom
+        })
+        .last()
+}
+
+fn grid_at_pixel(
+    window_regions: &[WindowDrawDetails],
+    font_dimensions: Dimensions,
+    x: f32,
+    y: f32,
+) -> Option<(u64, (u64, u64))> {
+    let details = window_details_at(window_regions, x, y)?;
+
+    let column = (x - details.region.left) as u64 / font_dimensions.width;
+    let row = (y - details.region.top) as u64 / font_dimensions.height;
+    Some((details.id, (column, row)))
+}
+
+#[cfg(test)]
+mod tests {
+    use skia_safe::Rect;
+
+    use super::*;
+
+    fn window_details(id: u64, region: Rect) -> WindowDrawDetails {
+        WindowDrawDetails {
+            id,
+            region,
+            floating_order: None,
+        }
+    }
+
+    #[test]
+    fn grid_at_pixel_finds_the_cell_in_the_topmost_grid() {
+        let font_dimensions = Dimensions::from((10u64, 20u64));
+        let window_regions = vec![
+            window_details(1, Rect::from_xywh(0.0, 0.0, 100.0, 100.0)),
+            window_details(2, Rect::from_xywh(50.0, 40.0, 30.0, 40.0)),
+        ];
+
+        assert_eq!(
+            grid_at_pixel(&window_regions, font_dimensions, 25.0, 45.0),
+            Some((1, (2, 2)))
+        );
+        assert_eq!(
+            grid_at_pixel(&window_regions, font_dimensions, 65.0, 45.0),
+            Some((2, (1, 0)))
+        );
+    }
+
+    #[test]
+    fn grid_at_pixel_is_none_outside_of_every_grid() {
+        let font_dimensions = Dimensions::from((10u64, 20u64));
+        let window_regions = vec![window_details(1, Rect::from_xywh(10.0, 10.0, 100.0, 100.0))];
+
+        assert_eq!(
+            grid_at_pixel(&window_regions, font_dimensions, 5.0, 50.0),
+            None
+        );
+        assert_eq!(
+            grid_at_pixel(&window_regions, font_dimensions, 50.0, 110.0),
+            None
+        );
+        assert_eq!(grid_at_pixel(&[], font_dimensions, 50.0, 50.0), None);
+    }
+}
@@ -22,6 +22,8 @@ pub struct LineFragment {
     pub window_top: u64,
     pub width: u64,
     pub style: Option<Arc<Style>>,
+    // Offsets of the cells in the fragment that were cleared and not drawn to since
+    pub blank_cells: Vec<u64>,
 }
 
 #[derive(Clone, Debug)]
@@ -452,9 +454,17 @@ impl RenderedWindow {
                         window_top,
                         width,
                         style,
+                        blank_cells,
                     } = line_fragment;
                     let grid_position = (window_left, window_top);
-                    grid_renderer.draw_foreground(canvas, text, grid_position, width, &style);
+                    grid_renderer.draw_foreground(
+                        canvas,
+                        text,
+                        grid_position,
+                        width,
+                        &style,
+                        &blank_cells,
+                    );
                 }
                 canvas.restore();
 
@@ -474,11 +484,9 @@ impl RenderedWindow {
                     width: font_width,
                     height: font_height,
                 } = grid_renderer.font_dimensions;
-                let scrolled_region = Rect::new(
-                    (left * font_width) as f32,
-                    (top * font_height) as f32,
-                    (right * font_width) as f32,
-                    (bottom * font_height) as f32,
+                let scrolled_region = grid_renderer.compute_grid_region(
+                    (left, top),
+                    (right.saturating_sub(left), bottom.saturating_sub(top)),
                 );
 
                 let mut translated_region = scrolled_region;
@@ -1,8 +1,9 @@
 use crate::{
     bridge::{SerialCommand, UiCommand},
+    editor::EditorCommand,
     event_aggregator::EVENT_AGGREGATOR,
     settings::SETTINGS,
-    window::KeyboardSettings,
+    window::{KeyboardSettings, WindowSettings},
 };
 use glutin::{
     event::{ElementState, Event, KeyEvent, WindowEvent},
@@ -88,9 +89,16 @@ impl KeyboardManager {
                                 // And a key was pressed
                                 if key_event.state == ElementState::Pressed {
                                     if let Some(keybinding) = self.maybe_get_keybinding(key_event) {
-                                        EVENT_AGGREGATOR.send(UiCommand::Serial(
-                                            SerialCommand::Keyboard(keybinding),
-                                        ));
+                                        if let Some(delta) = self.font_size_delta(key_event) {
+                                            EVENT_AGGREGATOR.send(EditorCommand::AdjustFontSize {
+                                                delta,
+                                                keybinding,
+                                            });
+                                        } else {
+                                            EVENT_AGGREGATOR.send(UiCommand::Serial(
+                                                SerialCommand::Keyboard(keybinding),
+                                            ));
+                                        }
                                     }
                                     next_dead_key = None;
                                 } else if key_event.state == ElementState::Released {
@@ -127,6 +135,20 @@ impl KeyboardManager {
         self.ignore_input_this_frame || (self.logo && !settings.use_logo)
     }
 
+    // With g:neovide_zoom_keys set, Ctrl+= and Ctrl+- zoom the font instead of being sent to
+    // Neovim. Ctrl++ is accepted as well since = and + share a key on most layouts.
+    fn font_size_delta(&self, key_event: &KeyEvent) -> Option<f32> {
+        if !self.ctrl || self.alt || self.logo || !SETTINGS.get::<WindowSettings>().zoom_keys {
+            return None;
+        }
+
+        match key_event.key_without_modifiers().to_text() {
+            Some("=") | Some("+") => Some(1.0),
+            Some("-") => Some(-1.0),
+            _ => None,
+        }
+    }
+
     fn maybe_get_keybinding(&self, key_event: &KeyEvent) -> Option<String> {
         // Determine if this key event represents a key which won't ever
         // present text.
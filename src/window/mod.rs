@@ -71,6 +71,9 @@ pub struct GlutinWindowWrapper {
     title: String,
     fullscreen: bool,
     font_changed_last_frame: bool,
+    normalize_text: bool,
+    max_fps: u64,
+    saved_ime_position: Option<PhysicalPosition<f32>>,
     saved_inner_size: PhysicalSize<u32>,
     saved_grid_size: Option<Dimensions>,
     size_at_startup: PhysicalSize<u32>,
@@ -113,8 +116,17 @@ impl GlutinWindowWrapper {
     }
 
     pub fn handle_title_changed(&mut self, new_title: String) {
-        self.title = new_title;
-        self.windowed_context.window().set_title(&self.title);
+        // Neovim sends an empty title when 'title' is unset.
+        let new_title = if new_title.is_empty() {
+            "Neovide".to_owned()
+        } else {
+            new_title
+        };
+
+        if new_title != self.title {
+            self.title = new_title;
+            self.windowed_context.window().set_title(&self.title);
+        }
     }
 
     pub fn send_font_names(&self) {
@@ -134,6 +146,8 @@ impl GlutinWindowWrapper {
 
     pub fn handle_focus_lost(&mut self) {
         EVENT_AGGREGATOR.send(UiCommand::Parallel(ParallelCommand::FocusLost));
+        // Draw the cursor as an outline right away instead of on the next unrelated frame
+        REDRAW_SCHEDULER.queue_next_frame();
     }
 
     pub fn handle_focus_gained(&mut self) {
@@ -210,6 +224,18 @@ impl GlutinWindowWrapper {
             self.renderer.window_padding = window_padding;
         }
 
+        if window_settings.normalize_text != self.normalize_text {
+            self.normalize_text = window_settings.normalize_text;
+            EVENT_AGGREGATOR.send(EditorCommand::SetNormalizeText(self.normalize_text));
+        }
+
+        if window_settings.max_fps != self.max_fps {
+            self.max_fps = window_settings.max_fps;
+            let min_frame_interval =
+                (self.max_fps > 0).then(|| Duration::from_secs_f32(1.0 / self.max_fps as f32));
+            REDRAW_SCHEDULER.set_min_frame_interval(min_frame_interval);
+        }
+
         if self.saved_inner_size != new_size || self.font_changed_last_frame || padding_changed {
             self.font_changed_last_frame = false;
             self.saved_inner_size = new_size;
@@ -223,6 +249,19 @@ impl GlutinWindowWrapper {
                 self.renderer.draw_frame(self.skia_renderer.canvas(), dt);
             self.skia_renderer.gr_context.flush(None);
             self.windowed_context.swap_buffers().unwrap();
+
+            // Open the IME candidate window right below the cursor. Frames are drawn while the
+            // cursor animates, so the platform is only told when the position actually changes.
+            let cursor_position = self.renderer.cursor_pixel_position();
+            let font_height = self.renderer.grid_renderer.font_dimensions.height;
+            let ime_position =
+                PhysicalPosition::new(cursor_position.x, cursor_position.y + font_height as f32);
+            if self.saved_ime_position != Some(ime_position) {
+                self.saved_ime_position = Some(ime_position);
+                self.windowed_context
+                    .window()
+                    .set_ime_position(ime_position);
+            }
         }
 
         // Wait until fonts are loaded, so we can set proper window size.
@@ -288,6 +327,9 @@ impl GlutinWindowWrapper {
 
     fn handle_scale_factor_update(&mut self, scale_factor: f64) {
         self.renderer.handle_os_scale_factor_change(scale_factor);
+        // The font dimensions change with the scale factor, so the grid size has to be
+        // recomputed the same way as for a font change.
+        self.font_changed_last_frame = true;
         EVENT_AGGREGATOR.send(EditorCommand::RedrawScreen);
     }
 
@@ -447,6 +489,9 @@ pub fn create_window() {
         title: String::from("Neovide"),
         fullscreen: false,
         font_changed_last_frame: false,
+        normalize_text: false,
+        max_fps: 0,
+        saved_ime_position: None,
         size_at_startup: initial_size,
         maximized_at_startup: maximized,
         saved_inner_size,
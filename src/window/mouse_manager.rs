@@ -74,6 +74,7 @@ pub struct MouseManager {
 
     has_moved: bool,
     position: PhysicalPosition<u32>,
+    pixel_position: PhysicalPosition<f32>,
     relative_position: PhysicalPosition<u32>,
 
     scroll_position: PhysicalPosition<f32>,
@@ -93,6 +94,7 @@ impl MouseManager {
             dragging: None,
             has_moved: false,
             position: PhysicalPosition::new(0, 0),
+            pixel_position: PhysicalPosition::new(0.0, 0.0),
             relative_position: PhysicalPosition::new(0, 0),
             drag_position: PhysicalPosition::new(0, 0),
             scroll_position: PhysicalPosition::new(0.0, 0.0),
@@ -117,6 +119,7 @@ impl MouseManager {
         }
 
         let position: PhysicalPosition<f32> = PhysicalPosition::new(x as f32, y as f32);
+        self.pixel_position = position;
 
         // If dragging, the relevant window (the one which we send all commands to) is the one
         // which the mouse drag started on. Otherwise its the top rendered window
@@ -130,18 +133,7 @@ impl MouseManager {
                         .id
             })
         } else {
-            // the rendered window regions are sorted by draw order, so the earlier windows in the
-            // list are drawn under the later ones
-            renderer
-                .window_regions
-                .iter()
-                .filter(|details| {
-                    position.x >= details.region.left
-                        && position.x < details.region.right
-                        && position.y >= details.region.top
-                        && position.y < details.region.bottom
-                })
-                .last()
+            renderer.window_details_at(position.x, position.y)
         };
 
         let global_bounds = relevant_window_details
@@ -236,11 +228,31 @@ impl MouseManager {
         }
     }
 
-    fn handle_line_scroll(&mut self, x: f32, y: f32, keyboard_manager: &KeyboardManager) {
+    fn handle_line_scroll(
+        &mut self,
+        x: f32,
+        y: f32,
+        keyboard_manager: &KeyboardManager,
+        renderer: &Renderer,
+    ) {
         if !self.enabled {
             return;
         }
 
+        // Scroll the grid under the pointer, or the one it was last over when it is outside of
+        // every grid, such as over the window padding
+        let (grid_id, position) = renderer
+            .grid_at_pixel(self.pixel_position.x, self.pixel_position.y)
+            .map(|(grid_id, (column, row))| (grid_id, (column as u32, row as u32)))
+            .unwrap_or_else(|| {
+                let grid_id = self
+                    .window_details_under_mouse
+                    .as_ref()
+                    .map(|details| details.id)
+                    .unwrap_or(0);
+                (grid_id, self.drag_position.into())
+            });
+
         let previous_y = self.scroll_position.y as i64;
         self.scroll_position.y += y;
         let new_y = self.scroll_position.y as i64;
@@ -254,12 +266,8 @@ impl MouseManager {
         if let Some(input_type) = vertical_input_type {
             let scroll_command: UiCommand = SerialCommand::Scroll {
                 direction: input_type.to_string(),
-                grid_id: self
-                    .window_details_under_mouse
-                    .as_ref()
-                    .map(|details| details.id)
-                    .unwrap_or(0),
-                position: self.drag_position.into(),
+                grid_id,
+                position,
                 modifier_string: keyboard_manager.format_modifier_string(true),
             }
             .into();
@@ -281,12 +289,8 @@ impl MouseManager {
         if let Some(input_type) = horizontal_input_type {
             let scroll_command: UiCommand = SerialCommand::Scroll {
                 direction: input_type.to_string(),
-                grid_id: self
-                    .window_details_under_mouse
-                    .as_ref()
-                    .map(|details| details.id)
-                    .unwrap_or(0),
-                position: self.drag_position.into(),
+                grid_id,
+                position,
                 modifier_string: keyboard_manager.format_modifier_string(true),
             }
             .into();
@@ -298,14 +302,16 @@ impl MouseManager {
 
     fn handle_pixel_scroll(
         &mut self,
-        (font_width, font_height): (u64, u64),
         (pixel_x, pixel_y): (f32, f32),
         keyboard_manager: &KeyboardManager,
+        renderer: &Renderer,
     ) {
+        let (font_width, font_height): (u64, u64) = renderer.grid_renderer.font_dimensions.into();
         self.handle_line_scroll(
             pixel_x / font_width as f32,
             pixel_y / font_height as f32,
             keyboard_manager,
+            renderer,
         );
     }
 
@@ -374,8 +380,7 @@ impl MouseManager {
                         // starting point
                         trace.last = location;
 
-                        let font_size = renderer.grid_renderer.font_dimensions.into();
-                        self.handle_pixel_scroll(font_size, delta, keyboard_manager);
+                        self.handle_pixel_scroll(delta, keyboard_manager, renderer);
                     }
                 }
 
@@ -442,7 +447,7 @@ impl MouseManager {
                         ..
                     },
                 ..
-            } => self.handle_line_scroll(*x, *y, keyboard_manager),
+            } => self.handle_line_scroll(*x, *y, keyboard_manager, renderer),
             Event::WindowEvent {
                 event:
                     WindowEvent::MouseWheel {
@@ -451,9 +456,9 @@ impl MouseManager {
                     },
                 ..
             } => self.handle_pixel_scroll(
-                renderer.grid_renderer.font_dimensions.into(),
                 (delta.x as f32, delta.y as f32),
                 keyboard_manager,
+                renderer,
             ),
             Event::WindowEvent {
                 event:
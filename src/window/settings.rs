@@ -4,6 +4,7 @@ use crate::{cmd_line::CmdLineSettings, settings::*};
 pub struct WindowSettings {
     pub refresh_rate: u64,
     pub refresh_rate_idle: u64,
+    pub max_fps: u64,
     pub no_idle: bool,
     pub transparency: f32,
     pub scale_factor: f32,
@@ -20,6 +21,8 @@ pub struct WindowSettings {
     pub padding_left: u32,
     pub padding_right: u32,
     pub padding_bottom: u32,
+    pub normalize_text: bool,
+    pub zoom_keys: bool,
 }
 
 impl Default for WindowSettings {
@@ -31,6 +34,7 @@ impl Default for WindowSettings {
             iso_layout: false,
             refresh_rate: 60,
             refresh_rate_idle: 5,
+            max_fps: 0,
             no_idle: SETTINGS.get::<CmdLineSettings>().no_idle,
             remember_window_size: true,
             remember_window_position: true,
@@ -43,6 +47,8 @@ impl Default for WindowSettings {
             padding_left: 0,
             padding_right: 0,
             padding_bottom: 0,
+            normalize_text: false,
+            zoom_keys: false,
         }
     }
 }